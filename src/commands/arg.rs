@@ -1,14 +1,22 @@
 use std::borrow::Borrow;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use derive_more::{From, IsVariant, Unwrap};
 use twilight_mention::ParseMention;
-use twilight_model::application::interaction::application_command::CommandOptionValue;
+use twilight_model::application::interaction::application_command::{
+    CommandInteractionDataResolved, CommandOptionValue,
+};
+use twilight_model::channel::Channel;
+use twilight_model::guild::Role;
+use twilight_model::id::marker::GuildMarker;
 use twilight_model::id::Id;
+use twilight_model::user::User;
 
-use crate::commands::builder::{ArgDesc, ArgKind};
-use crate::commands::CommandError;
+use crate::commands::builder::{ArgDesc, ArgKind, NumericalData};
+use crate::commands::{CommandError, CommandResult};
 use crate::utils::prelude::*;
+use crate::Context;
 
 pub mod types {
     use twilight_model::channel::{Attachment, Channel, Message};
@@ -54,6 +62,20 @@ impl<M, D> Ref<M, D> {
     pub fn from_obj(obj: D) -> Self {
         Self::Obj(Arc::new(obj))
     }
+
+    /// Returns the object variant if the id is found in `objects`, otherwise returns self.
+    fn or_from(self, objects: &HashMap<Id<M>, D>) -> Self
+    where
+        D: Clone,
+    {
+        match self {
+            Self::Id(id) => match objects.get(&id) {
+                Some(obj) => Self::from_obj(obj.clone()),
+                None => Self::Id(id),
+            },
+            obj => obj,
+        }
+    }
 }
 
 impl<M, D> IdExt<M> for Ref<M, D>
@@ -103,6 +125,49 @@ impl Args {
         pub fn mention -> types::ArgMention;
     );
 
+    /// Finds a user argument by name and returns the object,
+    /// from the argument itself, cache or fetched from client.
+    /// # Errors
+    /// Same as `Args::user`, or if the user could not be fetched.
+    pub async fn resolve_user(&self, ctx: &Context, name: &str) -> CommandResult<Arc<User>> {
+        match self.user(name)? {
+            Ref::Obj(user) => Ok(user),
+            Ref::Id(id) => Ok(Arc::new(ctx.user_from(id).await?)),
+        }
+    }
+
+    /// Finds a role argument by name and returns the object,
+    /// from the argument itself, cache or fetched from client.
+    /// # Errors
+    /// Same as `Args::role`, or if the role could not be fetched from the guild.
+    pub async fn resolve_role(
+        &self,
+        ctx: &Context,
+        guild_id: Id<GuildMarker>,
+        name: &str,
+    ) -> CommandResult<Arc<Role>> {
+        match self.role(name)? {
+            Ref::Obj(role) => Ok(role),
+            Ref::Id(id) => {
+                let role = ctx.roles_from(guild_id, &[id]).await?.pop();
+                Ok(Arc::new(role.with_context(|| {
+                    format!("Role '{id}' not found in guild '{guild_id}'")
+                })?))
+            },
+        }
+    }
+
+    /// Finds a channel argument by name and returns the object,
+    /// from the argument itself, cache or fetched from client.
+    /// # Errors
+    /// Same as `Args::channel`, or if the channel could not be fetched.
+    pub async fn resolve_channel(&self, ctx: &Context, name: &str) -> CommandResult<Arc<Channel>> {
+        match self.channel(name)? {
+            Ref::Obj(channel) => Ok(channel),
+            Ref::Id(id) => Ok(Arc::new(ctx.channel_from(id).await?)),
+        }
+    }
+
     /// Finds argument value by argument name.
     pub fn get(&self, name: &str) -> Option<&ArgValue> {
         self.as_ref()
@@ -111,6 +176,11 @@ impl Args {
             .map(|a| &a.value)
     }
 
+    /// Extract the arguments into a typed struct, see `FromArgs`.
+    pub fn extract<T: FromArgs>(&self) -> CommandResult<T> {
+        T::from_args(self)
+    }
+
     /// Returns the inner box.
     pub fn into_inner(self) -> Box<[Arg]> {
         self.0
@@ -175,9 +245,8 @@ impl ArgValue {
     );
 
     /// Create a value from value kind and text.
+    /// Choices can be given by their name as well as by their value.
     pub fn from_kind(kind: &ArgKind, text: &str) -> AnyResult<Self> {
-        // TODO: Ensure data parameters.
-
         /// Try to parse text as a discord mention, otherwise try to parse text as an id number.
         fn parse_mention_or_id<F, A, B>(text: &str, variant: F) -> AnyResult<ArgValue>
         where
@@ -202,9 +271,20 @@ impl ArgValue {
                     .parse()
                     .context("Bool arg parse error")?,
             ),
-            ArgKind::Number(_) => Self::Number(text.parse().context("Number arg parse error")?),
-            ArgKind::Integer(_) => Self::Integer(text.parse().context("Integer arg parse error")?),
-            ArgKind::String(_) => Self::String(text.to_string().into_boxed_str()),
+            ArgKind::Number(data) => Self::Number(match choice_value(&data.choices, text) {
+                Some(val) => *val,
+                None => text.parse().context("Number arg parse error")?,
+            }),
+            ArgKind::Integer(data) => Self::Integer(match choice_value(&data.choices, text) {
+                Some(val) => *val,
+                None => text.parse().context("Integer arg parse error")?,
+            }),
+            ArgKind::String(data) => Self::String(
+                choice_value(&data.choices, text)
+                    .map_or(text, String::as_str)
+                    .to_string()
+                    .into_boxed_str(),
+            ),
             ArgKind::Channel(_) => {
                 parse_mention_or_id(text, Self::Channel).context("Channel arg parse error")?
             },
@@ -227,6 +307,76 @@ impl ArgValue {
 
         Ok(val)
     }
+
+    /// Check that the value satisfies the constraints of the argument kind,
+    /// which Discord enforces only for slash commands.
+    /// Channel types are not checked, because the channel may have to be fetched.
+    /// # Errors
+    /// Returns a description of the violated constraint.
+    pub fn check(&self, kind: &ArgKind) -> Result<(), String> {
+        match (self, kind) {
+            (Self::Number(val), ArgKind::Number(data)) => check_numerical(*val, data),
+            (Self::Integer(val), ArgKind::Integer(data)) => check_numerical(*val, data),
+            (Self::String(val), ArgKind::String(data)) => {
+                let len = val.chars().count();
+                if let Some(min) = data.min_length.filter(|min| len < usize::from(*min)) {
+                    return Err(format!("must be at least {min} characters long"));
+                }
+                if let Some(max) = data.max_length.filter(|max| len > usize::from(*max)) {
+                    return Err(format!("must be at most {max} characters long"));
+                }
+                if !data.choices.is_empty() && !data.choices.iter().any(|(_, c)| **c == **val) {
+                    return Err(choices_error(&data.choices));
+                }
+                Ok(())
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Replace an id with the object that Discord resolved for the interaction, if any.
+    /// Channels are left as ids, because only partial channels are resolved.
+    pub fn resolved(self, resolved: &CommandInteractionDataResolved) -> Self {
+        match self {
+            Self::Message(val) => Self::Message(val.or_from(&resolved.messages)),
+            Self::Attachment(val) => Self::Attachment(val.or_from(&resolved.attachments)),
+            Self::User(val) => Self::User(val.or_from(&resolved.users)),
+            Self::Role(val) => Self::Role(val.or_from(&resolved.roles)),
+            val => val,
+        }
+    }
+}
+
+/// Returns the value of the choice named `text`, ignoring case.
+fn choice_value<'a, T>(choices: &'a [(String, T)], text: &str) -> Option<&'a T> {
+    let text = text.trim();
+    choices
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(text))
+        .map(|(_, val)| val)
+}
+
+/// Check that a number is within the range and one of the choices, if any.
+fn check_numerical<T>(val: T, data: &NumericalData<T>) -> Result<(), String>
+where
+    T: PartialOrd + std::fmt::Display + Copy,
+{
+    if let Some(min) = data.min.filter(|min| val < *min) {
+        return Err(format!("must be at least {min}"));
+    }
+    if let Some(max) = data.max.filter(|max| val > *max) {
+        return Err(format!("must be at most {max}"));
+    }
+    if !data.choices.is_empty() && !data.choices.iter().any(|(_, c)| *c == val) {
+        return Err(choices_error(&data.choices));
+    }
+    Ok(())
+}
+
+/// Returns an error listing the names of the `choices`.
+fn choices_error<T>(choices: &[(String, T)]) -> String {
+    let names: Vec<_> = choices.iter().map(|(name, _)| name.as_str()).collect();
+    format!("must be one of: {}", names.join(", "))
 }
 
 impl TryFrom<CommandOptionValue> for ArgValue {
@@ -243,7 +393,9 @@ impl TryFrom<CommandOptionValue> for ArgValue {
             CommandOptionValue::Attachment(id) => Ok(Self::Attachment(Ref::Id(id))),
             CommandOptionValue::User(id) => Ok(Self::User(Ref::Id(id))),
             CommandOptionValue::Role(id) => Ok(Self::Role(Ref::Id(id))),
-            CommandOptionValue::Focused(_s, _c) => todo!(), // FIXME: To be implemented
+            CommandOptionValue::Focused(..) => {
+                Err("Cannot convert focused autocomplete option to argument value")
+            },
             CommandOptionValue::SubCommand(_) | CommandOptionValue::SubCommandGroup(_) => {
                 Err("Cannot convert subcommand or group to argument value")
             },
@@ -309,3 +461,215 @@ where
         self.as_ref().and_then(|v| v.borrow().mention())
     }
 }
+
+/// Typed struct of command arguments.
+/// Implement with the `from_args!` macro, so that the options and the extraction use the same names.
+pub trait FromArgs: Sized {
+    /// Returns the options that the struct is extracted from.
+    fn options() -> Vec<ArgDesc>;
+
+    /// Extract the struct from arguments.
+    /// # Errors
+    /// Returns `CommandError::MissingArgs` or `CommandError::ArgsMismatch` for a field that could not be extracted.
+    fn from_args(args: &Args) -> CommandResult<Self>;
+}
+
+/// Field type of a `FromArgs` struct.
+pub trait FromArgValue: Sized {
+    /// Convert an argument value that may be missing.
+    fn from_arg(value: Option<&ArgValue>) -> CommandResult<Self>;
+}
+
+/// Implements `FromArgValue` for the types of the argument variants.
+macro impl_from_arg_value($( $t:ty => $method:ident );* $(;)?) {
+    $(
+        impl FromArgValue for $t {
+            fn from_arg(value: Option<&ArgValue>) -> CommandResult<Self> {
+                value
+                    .ok_or(CommandError::MissingArgs)?
+                    .$method()
+                    .ok_or(CommandError::ArgsMismatch)
+            }
+        }
+    )*
+}
+
+impl_from_arg_value!(
+    types::ArgBool => bool;
+    types::ArgNumber => number;
+    types::ArgInteger => integer;
+    types::ArgString => string;
+    types::ArgChannel => channel;
+    types::ArgMessage => message;
+    types::ArgAttachment => attachment;
+    types::ArgUser => user;
+    types::ArgRole => role;
+    types::ArgMention => mention;
+);
+
+impl FromArgValue for String {
+    fn from_arg(value: Option<&ArgValue>) -> CommandResult<Self> {
+        types::ArgString::from_arg(value).map(Into::into)
+    }
+}
+
+/// Optional arguments are `None` if missing.
+impl<T: FromArgValue> FromArgValue for Option<T> {
+    fn from_arg(value: Option<&ArgValue>) -> CommandResult<Self> {
+        value.map(|v| T::from_arg(Some(v))).transpose()
+    }
+}
+
+/// Define a struct of command arguments and implement `FromArgs` for it.
+/// Each field is declared with its type and the option it is extracted from.
+/// Optional arguments should be of type `Option<T>`.
+///
+/// ```ignore
+/// from_args! {
+///     struct FuelArgs {
+///         stint: i64 = integer("stint-minutes", "Length of the stint in minutes.").required(),
+///         consumption: Option<f64> = number("consumption", "Fuel per lap."),
+///     }
+/// }
+///
+/// command("fuel", "Calculate fuel.").options_from::<FuelArgs>();
+/// let FuelArgs { stint, consumption } = req.args.extract()?;
+/// ```
+pub macro from_args(
+    $(#[$meta:meta])*
+    $vis:vis struct $name:ident {
+        $( $(#[$field_meta:meta])* $field_vis:vis $field:ident: $t:ty = $desc:expr ),* $(,)?
+    }
+) {
+    $(#[$meta])*
+    $vis struct $name {
+        $( $(#[$field_meta])* $field_vis $field: $t, )*
+    }
+
+    impl FromArgs for $name {
+        fn options() -> Vec<ArgDesc> {
+            vec![$( ArgDesc::from($desc) ),*]
+        }
+
+        fn from_args(args: &Args) -> CommandResult<Self> {
+            Ok(Self {
+                $( $field: FromArgValue::from_arg(args.get(ArgDesc::from($desc).name))?, )*
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::id::marker::UserMarker;
+
+    use super::*;
+    use crate::commands::builder::{integer, string, StringData};
+
+    #[test]
+    fn ref_or_from() {
+        let objects = HashMap::from([(Id::new(1), "one".to_string())]);
+
+        let found = Ref::<UserMarker, String>::Id(Id::new(1)).or_from(&objects);
+        assert!(matches!(found, Ref::Obj(obj) if *obj == "one"));
+
+        let missing = Ref::<UserMarker, String>::Id(Id::new(2)).or_from(&objects);
+        assert!(matches!(missing, Ref::Id(id) if id == Id::new(2)));
+
+        // Objects are not replaced.
+        let obj = Ref::<UserMarker, String>::from_obj("other".to_string()).or_from(&objects);
+        assert!(matches!(obj, Ref::Obj(obj) if *obj == "other"));
+    }
+
+    #[test]
+    fn resolved_values() {
+        let resolved: CommandInteractionDataResolved = serde_json::from_value(serde_json::json!({
+            "users": {
+                "2": { "id": "2", "username": "user", "discriminator": "0001", "avatar": null },
+            },
+        }))
+        .unwrap();
+
+        let value = ArgValue::User(Ref::Id(Id::new(2))).resolved(&resolved);
+        assert!(matches!(value, ArgValue::User(Ref::Obj(user)) if user.name == "user"));
+
+        let value = ArgValue::User(Ref::Id(Id::new(3))).resolved(&resolved);
+        assert!(matches!(value, ArgValue::User(Ref::Id(_))));
+
+        // Only partial channels are resolved, so they are left as ids.
+        let value = ArgValue::Channel(Ref::Id(Id::new(2))).resolved(&resolved);
+        assert!(matches!(value, ArgValue::Channel(Ref::Id(_))));
+    }
+
+    #[test]
+    fn classic_constraints() {
+        let integer = ArgKind::Integer(NumericalData {
+            min: Some(0),
+            max: Some(100),
+            ..Default::default()
+        });
+        let value = ArgValue::from_kind(&integer, "5000").unwrap();
+        assert_eq!(
+            Err("must be at most 100".to_string()),
+            value.check(&integer)
+        );
+        let value = ArgValue::from_kind(&integer, "50").unwrap();
+        assert_eq!(Ok(()), value.check(&integer));
+
+        let string = ArgKind::String(StringData {
+            choices: vec![("UTC+2".to_string(), "+2".to_string())],
+            ..Default::default()
+        });
+        let value = ArgValue::from_kind(&string, "utc+2").unwrap();
+        assert_eq!(Some("+2".into()), value.string());
+        assert_eq!(Ok(()), value.check(&string));
+        let value = ArgValue::from_kind(&string, "+3").unwrap();
+        assert_eq!(
+            Err("must be one of: UTC+2".to_string()),
+            value.check(&string)
+        );
+
+        let string = ArgKind::String(StringData {
+            min_length: Some(2),
+            ..Default::default()
+        });
+        let value = ArgValue::from_kind(&string, "a").unwrap();
+        assert_eq!(
+            Err("must be at least 2 characters long".to_string()),
+            value.check(&string)
+        );
+    }
+
+    from_args! {
+        struct TestArgs {
+            count: i64 = integer("count", "Count.").required(),
+            text: Option<String> = string("text", "Text."),
+        }
+    }
+
+    #[test]
+    fn typed_args() {
+        let names: Vec<_> = TestArgs::options().iter().map(|d| d.name).collect();
+        assert_eq!(vec!["count", "text"], names);
+
+        let args = Args::from(vec![Arg {
+            name: "count".to_string(),
+            value: ArgValue::Integer(3),
+        }]);
+        let TestArgs { count, text } = args.extract().unwrap();
+        assert_eq!((3, None), (count, text));
+
+        let args = Args::from(vec![Arg {
+            name: "count".to_string(),
+            value: ArgValue::Bool(true),
+        }]);
+        assert_eq!(
+            Some(CommandError::ArgsMismatch),
+            args.extract::<TestArgs>().err()
+        );
+        assert_eq!(
+            Some(CommandError::MissingArgs),
+            Args::default().extract::<TestArgs>().err()
+        );
+    }
+}
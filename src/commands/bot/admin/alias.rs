@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+
+use twilight_model::id::marker::GuildMarker;
+use twilight_model::id::Id;
+
+use crate::commands::prelude::*;
+use crate::parser;
+use crate::utils::prelude::*;
+
+/// Command: Manage guild command aliases.
+pub struct Alias;
+
+impl Alias {
+    pub fn command() -> impl Into<BaseCommand> {
+        use crate::commands::builder::*;
+
+        command("alias", "Manage command aliases.")
+            .attach(Self::classic)
+            .attach(Self::slash)
+            .permissions(Permissions::ADMINISTRATOR)
+            .category(Category::Admin)
+            .option(
+                sub("add", "Add or replace a command alias.")
+                    .attach(Add::classic)
+                    .attach(Add::slash)
+                    .option(
+                        string("name", "Name of the alias.")
+                            .required()
+                            .max_length(32),
+                    )
+                    .option(
+                        string("command", "Command to run, with any preset arguments.")
+                            .required()
+                            .rest(),
+                    ),
+            )
+            .option(
+                sub("remove", "Remove a command alias.")
+                    .attach(Remove::classic)
+                    .attach(Remove::slash)
+                    .option(string("name", "Name of the alias.").required()),
+            )
+            .option(
+                sub("list", "List command aliases.")
+                    .attach(List::classic)
+                    .attach(List::slash),
+            )
+    }
+
+    async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
+        List::classic(ctx, req).await
+    }
+
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        List::slash(ctx, req).await
+    }
+}
+
+/// Command: Add or replace an alias.
+struct Add;
+
+impl Add {
+    fn uber(
+        ctx: &Context,
+        args: &Args,
+        guild_id: Option<Id<GuildMarker>>,
+    ) -> CommandResult<String> {
+        let Some(guild_id) = guild_id else {
+            return Err(CommandError::Disabled);
+        };
+
+        let name = args.string("name")?;
+        let name = name.trim();
+        let target = args.string("command")?;
+        let target = target.trim();
+
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(CommandError::UnexpectedArgs(format!(
+                "Alias name must be a single word: '{name}'"
+            )));
+        }
+
+        // Aliases must not shadow commands.
+        if ctx.commands.get(name).is_some() {
+            return Err(CommandError::UnexpectedArgs(format!(
+                "Alias name is already a command: '{name}'"
+            )));
+        }
+
+        // Aliases must point to commands, so they cannot point to other aliases.
+        let (command, _) = parser::split_once_whitespace(target);
+        if ctx.commands.get(command).is_none() {
+            return Err(CommandError::UnexpectedArgs(format!(
+                "Alias target does not start with a command: '{command}'"
+            )));
+        }
+
+        ctx.config.guild_settings_with(guild_id, |s| {
+            s.aliases.insert(name.to_string(), target.to_string());
+            Ok(())
+        })?;
+
+        info!("Alias '{name}' added for '{target}' in guild '{guild_id}'");
+
+        Ok(format!("Alias `{name}` now runs `{target}`."))
+    }
+
+    async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
+        let content = Self::uber(&ctx, &req.args, req.message.guild_id)?;
+
+        Ok(Response::reply(ctx, req, content))
+    }
+
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        let content = Self::uber(&ctx, &req.args, req.interaction.guild_id)?;
+
+        Ok(Response::reply(ctx, req, content))
+    }
+}
+
+/// Command: Remove an alias.
+struct Remove;
+
+impl Remove {
+    fn uber(
+        ctx: &Context,
+        args: &Args,
+        guild_id: Option<Id<GuildMarker>>,
+    ) -> CommandResult<String> {
+        let Some(guild_id) = guild_id else {
+            return Err(CommandError::Disabled);
+        };
+
+        let name = args.string("name")?;
+        let name = name.trim();
+
+        let removed = ctx
+            .config
+            .guild_settings_with(guild_id, |s| Ok(s.aliases.remove(name)))?;
+
+        Ok(match removed {
+            Some(_) => format!("Alias `{name}` removed."),
+            None => format!("Alias `{name}` not found :|"),
+        })
+    }
+
+    async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
+        let content = Self::uber(&ctx, &req.args, req.message.guild_id)?;
+
+        Ok(Response::reply(ctx, req, content))
+    }
+
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        let content = Self::uber(&ctx, &req.args, req.interaction.guild_id)?;
+
+        Ok(Response::reply(ctx, req, content))
+    }
+}
+
+/// Command: List all aliases of the guild.
+struct List;
+
+impl List {
+    fn uber(ctx: &Context, guild_id: Option<Id<GuildMarker>>) -> CommandResult<String> {
+        let Some(guild_id) = guild_id else {
+            return Err(CommandError::Disabled);
+        };
+
+        let mut guild = ctx.config.guild(guild_id);
+        let aliases: BTreeMap<_, _> = guild.aliases()?.iter().collect();
+
+        if aliases.is_empty() {
+            return Ok("No aliases set.".to_string());
+        }
+
+        Ok(aliases
+            .into_iter()
+            .map(|(name, target)| format!("`{name}` → `{target}`"))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
+        let content = Self::uber(&ctx, req.message.guild_id)?;
+
+        Ok(Response::reply(ctx, req, content))
+    }
+
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        let content = Self::uber(&ctx, req.interaction.guild_id)?;
+
+        Ok(Response::reply(ctx, req, content))
+    }
+}
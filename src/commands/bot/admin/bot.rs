@@ -15,26 +15,27 @@ impl Bot {
             .attach(Self::classic)
             .attach(Self::slash)
             .permissions(Permissions::ADMINISTRATOR)
+            .category(Category::Admin)
             .option(
                 sub("say", "Post a message by the bot.")
                     .attach(Say::classic)
                     .attach(Say::slash)
-                    .option(string("text", "What to say.").required()),
+                    .option(string("text", "What to say.").required().rest()),
             )
             .option(
                 sub("edit", "Edit an existing bot message.")
                     .attach(Edit::classic)
                     .option(message("message", "Message to edit.").required())
-                    .option(string("text", "New content.").required()),
+                    .option(string("text", "New content.").required().rest()),
             )
     }
 
-    async fn classic(_ctx: Context, _req: ClassicRequest) -> CommandResponse {
-        todo!();
+    async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
+        req.reply_usage(&ctx).await
     }
 
-    async fn slash(_ctx: Context, _req: SlashRequest) -> CommandResponse {
-        todo!();
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        req.reply_usage(&ctx).await
     }
 }
 
@@ -0,0 +1,419 @@
+use std::collections::BTreeMap;
+
+use twilight_model::id::marker::GuildMarker;
+use twilight_model::id::Id;
+
+use crate::commands::prelude::*;
+use crate::config::{CommandSettings, PermissionOverrides};
+use crate::utils::prelude::*;
+
+/// Name of this command, which cannot be restricted to avoid locking admins out.
+const NAME: &str = "commands";
+
+/// Command: Manage guild command restrictions.
+pub struct ManageCommands;
+
+impl ManageCommands {
+    pub fn command() -> impl Into<BaseCommand> {
+        use crate::commands::builder::*;
+
+        let path = || {
+            string("command", "Command path, such as `voice play`.")
+                .required()
+                .autocomplete(Self::autocomplete)
+        };
+
+        command(NAME, "Manage commands in this guild.")
+            .attach(Self::slash)
+            .permissions(Permissions::ADMINISTRATOR)
+            .category(Category::Admin)
+            .option(
+                sub("enable", "Enable a command.")
+                    .attach(Enable::slash)
+                    .option(path()),
+            )
+            .option(
+                sub("disable", "Disable a command.")
+                    .attach(Disable::slash)
+                    .option(path()),
+            )
+            .option(
+                sub("allow", "Only allow a command in a channel or for a role.")
+                    .attach(Allow::slash)
+                    .option(path())
+                    .option(channel("channel", "Channel to allow."))
+                    .option(role("role", "Role to allow.")),
+            )
+            .option(
+                sub("deny", "Deny a command in a channel or for a role.")
+                    .attach(Deny::slash)
+                    .option(path())
+                    .option(channel("channel", "Channel to deny."))
+                    .option(role("role", "Role to deny.")),
+            )
+            .option(
+                sub(
+                    "grant",
+                    "Grant a command to a role or user regardless of permissions.",
+                )
+                .attach(Grant::slash)
+                .option(path())
+                .option(role("role", "Role to grant."))
+                .option(user("user", "User to grant.")),
+            )
+            .option(
+                sub(
+                    "revoke",
+                    "Deny a command from a role or user regardless of permissions.",
+                )
+                .attach(Revoke::slash)
+                .option(path())
+                .option(role("role", "Role to deny."))
+                .option(user("user", "User to deny.")),
+            )
+            .option(
+                sub(
+                    "reset",
+                    "Remove all restrictions and overrides of a command.",
+                )
+                .attach(Reset::slash)
+                .option(path()),
+            )
+            .option(sub("list", "List command restrictions.").attach(List::slash))
+    }
+
+    async fn autocomplete(ctx: Context, req: AutocompleteRequest) -> AutocompleteResponse {
+        let input = req.input.trim().to_lowercase();
+
+        Ok(ctx
+            .commands
+            .paths()
+            .into_iter()
+            .filter(|path| path.contains(&input) && !is_protected(path))
+            .map(|path| (path.clone(), ArgValue::String(path.into())))
+            .collect())
+    }
+
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        List::slash(ctx, req).await
+    }
+
+    /// Modify restrictions of the command path given in the arguments.
+    fn update(
+        ctx: &Context,
+        req: &SlashRequest,
+        f: impl Fn(&mut CommandSettings),
+    ) -> CommandResult<String> {
+        let (guild_id, path) = Self::target(ctx, req)?;
+
+        ctx.config.guild_settings_with(guild_id, |s| {
+            let settings = s.commands.entry(path.clone()).or_default();
+            f(settings);
+
+            // Keep the settings file clean.
+            if settings.is_empty() {
+                s.commands.remove(&path);
+            }
+
+            Ok(())
+        })?;
+
+        info!("Command '{path}' restrictions updated in guild '{guild_id}'");
+
+        Ok(path)
+    }
+
+    /// Modify permission overrides of the command path given in the arguments.
+    fn update_overrides(
+        ctx: &Context,
+        req: &SlashRequest,
+        f: impl Fn(&mut PermissionOverrides),
+    ) -> CommandResult<String> {
+        let (guild_id, path) = Self::target(ctx, req)?;
+
+        ctx.config.guild_settings_with(guild_id, |s| {
+            let overrides = s.permissions.entry(path.clone()).or_default();
+            f(overrides);
+
+            // Keep the settings file clean.
+            if overrides.is_empty() {
+                s.permissions.remove(&path);
+            }
+
+            Ok(())
+        })?;
+
+        info!("Command '{path}' permission overrides updated in guild '{guild_id}'");
+
+        Ok(path)
+    }
+
+    /// Returns the guild and the validated command path given in the arguments.
+    fn target(ctx: &Context, req: &SlashRequest) -> CommandResult<(Id<GuildMarker>, String)> {
+        let Some(guild_id) = req.interaction.guild_id else {
+            return Err(CommandError::Disabled);
+        };
+
+        let path = req.args.string("command")?;
+        let path = path.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        if is_protected(&path) {
+            return Err(CommandError::UnexpectedArgs(format!(
+                "Command '{NAME}' cannot be restricted"
+            )));
+        }
+
+        if !ctx.commands.paths().contains(&path) {
+            return Err(CommandError::NotFound(format!(
+                "Command '{path}' does not exist"
+            )));
+        }
+
+        Ok((guild_id, path))
+    }
+}
+
+/// Returns true if the command path cannot be restricted.
+fn is_protected(path: &str) -> bool {
+    path.split_whitespace().next() == Some(NAME)
+}
+
+/// Command: Enable a command.
+struct Enable;
+
+impl Enable {
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        let path = ManageCommands::update(&ctx, &req, |s| s.disabled = false)?;
+        Ok(Response::reply(
+            ctx,
+            req,
+            format!("Command `{path}` enabled."),
+        ))
+    }
+}
+
+/// Command: Disable a command.
+struct Disable;
+
+impl Disable {
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        let path = ManageCommands::update(&ctx, &req, |s| s.disabled = true)?;
+        Ok(Response::reply(
+            ctx,
+            req,
+            format!("Command `{path}` disabled."),
+        ))
+    }
+}
+
+/// Command: Allow a command only in a channel or for a role.
+struct Allow;
+
+impl Allow {
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        let channel_id = req.args.channel("channel").ok().map(|c| c.id());
+        let role_id = req.args.role("role").ok().map(|r| r.id());
+
+        if channel_id.is_none() && role_id.is_none() {
+            return Err(CommandError::MissingArgs);
+        }
+
+        let path = ManageCommands::update(&ctx, &req, |s| {
+            if let Some(id) = channel_id {
+                s.deny_channels.remove(&id);
+                s.allow_channels.insert(id);
+            }
+            if let Some(id) = role_id {
+                s.deny_roles.remove(&id);
+                s.allow_roles.insert(id);
+            }
+        })?;
+
+        Ok(Response::reply(
+            ctx,
+            req,
+            format!("Command `{path}` restrictions updated."),
+        ))
+    }
+}
+
+/// Command: Deny a command in a channel or for a role.
+struct Deny;
+
+impl Deny {
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        let channel_id = req.args.channel("channel").ok().map(|c| c.id());
+        let role_id = req.args.role("role").ok().map(|r| r.id());
+
+        if channel_id.is_none() && role_id.is_none() {
+            return Err(CommandError::MissingArgs);
+        }
+
+        let path = ManageCommands::update(&ctx, &req, |s| {
+            if let Some(id) = channel_id {
+                s.allow_channels.remove(&id);
+                s.deny_channels.insert(id);
+            }
+            if let Some(id) = role_id {
+                s.allow_roles.remove(&id);
+                s.deny_roles.insert(id);
+            }
+        })?;
+
+        Ok(Response::reply(
+            ctx,
+            req,
+            format!("Command `{path}` restrictions updated."),
+        ))
+    }
+}
+
+/// Command: Grant a command to a role or user regardless of their permissions.
+struct Grant;
+
+impl Grant {
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        let role_id = req.args.role("role").ok().map(|r| r.id());
+        let user_id = req.args.user("user").ok().map(|u| u.id());
+
+        if role_id.is_none() && user_id.is_none() {
+            return Err(CommandError::MissingArgs);
+        }
+
+        let path = ManageCommands::update_overrides(&ctx, &req, |o| {
+            if let Some(id) = role_id {
+                o.deny_roles.remove(&id);
+                o.allow_roles.insert(id);
+            }
+            if let Some(id) = user_id {
+                o.deny_users.remove(&id);
+                o.allow_users.insert(id);
+            }
+        })?;
+
+        Ok(Response::reply(
+            ctx,
+            req,
+            format!("Command `{path}` permission overrides updated."),
+        ))
+    }
+}
+
+/// Command: Deny a command from a role or user regardless of their permissions.
+struct Revoke;
+
+impl Revoke {
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        let role_id = req.args.role("role").ok().map(|r| r.id());
+        let user_id = req.args.user("user").ok().map(|u| u.id());
+
+        if role_id.is_none() && user_id.is_none() {
+            return Err(CommandError::MissingArgs);
+        }
+
+        let path = ManageCommands::update_overrides(&ctx, &req, |o| {
+            if let Some(id) = role_id {
+                o.allow_roles.remove(&id);
+                o.deny_roles.insert(id);
+            }
+            if let Some(id) = user_id {
+                o.allow_users.remove(&id);
+                o.deny_users.insert(id);
+            }
+        })?;
+
+        Ok(Response::reply(
+            ctx,
+            req,
+            format!("Command `{path}` permission overrides updated."),
+        ))
+    }
+}
+
+/// Command: Remove all restrictions and permission overrides of a command.
+struct Reset;
+
+impl Reset {
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        ManageCommands::update(&ctx, &req, |s| *s = CommandSettings::default())?;
+        let path =
+            ManageCommands::update_overrides(&ctx, &req, |o| *o = PermissionOverrides::default())?;
+        Ok(Response::reply(
+            ctx,
+            req,
+            format!("Command `{path}` restrictions removed."),
+        ))
+    }
+}
+
+/// Command: List command restrictions of the guild.
+struct List;
+
+impl List {
+    fn uber(ctx: &Context, guild_id: Option<Id<GuildMarker>>) -> CommandResult<String> {
+        let Some(guild_id) = guild_id else {
+            return Err(CommandError::Disabled);
+        };
+
+        let mut guild = ctx.config.guild(guild_id);
+        let settings = guild.settings()?;
+
+        // Restrictions and permission overrides, sorted by command path.
+        let mut commands: BTreeMap<_, (Option<&CommandSettings>, Option<&PermissionOverrides>)> =
+            BTreeMap::new();
+        for (path, s) in settings.commands.iter() {
+            commands.entry(path).or_default().0 = Some(s);
+        }
+        for (path, o) in settings.permissions.iter() {
+            commands.entry(path).or_default().1 = Some(o);
+        }
+
+        if commands.is_empty() {
+            return Ok("No command restrictions set.".to_string());
+        }
+
+        Ok(commands
+            .into_iter()
+            .map(|(path, (s, o))| {
+                let mut parts = Vec::new();
+                let s = s.cloned().unwrap_or_default();
+                let o = o.cloned().unwrap_or_default();
+
+                if s.disabled {
+                    parts.push("disabled".to_string());
+                }
+
+                let channels: Vec<_> = (s.allow_channels.iter().map(|id| format!("+<#{id}>")))
+                    .chain(s.deny_channels.iter().map(|id| format!("-<#{id}>")))
+                    .collect();
+                if !channels.is_empty() {
+                    parts.push(format!("channels: {}", channels.join(" ")));
+                }
+
+                let roles: Vec<_> = (s.allow_roles.iter().map(|id| format!("+<@&{id}>")))
+                    .chain(s.deny_roles.iter().map(|id| format!("-<@&{id}>")))
+                    .collect();
+                if !roles.is_empty() {
+                    parts.push(format!("roles: {}", roles.join(" ")));
+                }
+
+                let overrides: Vec<_> = (o.allow_roles.iter().map(|id| format!("+<@&{id}>")))
+                    .chain(o.deny_roles.iter().map(|id| format!("-<@&{id}>")))
+                    .chain(o.allow_users.iter().map(|id| format!("+<@{id}>")))
+                    .chain(o.deny_users.iter().map(|id| format!("-<@{id}>")))
+                    .collect();
+                if !overrides.is_empty() {
+                    parts.push(format!("overrides: {}", overrides.join(" ")));
+                }
+
+                format!("`{path}`: {}", parts.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        let content = Self::uber(&ctx, req.interaction.guild_id)?;
+        Ok(Response::reply(ctx, req, content))
+    }
+}
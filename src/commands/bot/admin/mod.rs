@@ -1,3 +1,5 @@
+pub mod alias;
 pub mod bot;
+pub mod manage;
 pub mod roles;
 pub mod silence;
@@ -1,10 +1,13 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use twilight_gateway::Event;
 use twilight_http::request::channel::reaction::RequestReactionType;
-use twilight_model::application::interaction::{Interaction, InteractionData};
 use twilight_model::channel::message::component::{
     ActionRow, Button, ButtonStyle, SelectMenu, SelectMenuOption,
 };
-use twilight_model::channel::message::{Component, MessageFlags, ReactionType};
+use twilight_model::channel::message::{Component, ReactionType};
 use twilight_model::channel::Message;
 use twilight_model::gateway::payload::incoming::RoleUpdate;
 use twilight_model::guild::Permissions;
@@ -15,13 +18,69 @@ use twilight_model::id::marker::{
     ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker,
 };
 use twilight_model::id::Id;
-use twilight_util::builder::InteractionResponseDataBuilder;
 
 use crate::commands::prelude::*;
 use crate::config::ReactionRole;
 use crate::utils;
 use crate::utils::prelude::*;
 
+/// Prefix of the reaction-roles setup component ids.
+const COMPONENT_PREFIX: &str = "roles:";
+
+/// Live reaction-roles setup sessions, by the session id in the component ids.
+static SESSIONS: Mutex<BTreeMap<u32, UnboundedSender<ComponentRequest>>> =
+    Mutex::new(BTreeMap::new());
+
+/// Live reaction-roles setup, receiving the component requests from the component handler.
+/// The setup state only lives in its process, so the session ends when it is dropped.
+struct Session {
+    id: u32,
+    requests: UnboundedReceiver<ComponentRequest>,
+}
+
+impl Session {
+    /// Start a new session with an unused id.
+    fn start() -> Self {
+        let (tx, requests) = mpsc::unbounded_channel();
+        let mut sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        let id = std::iter::repeat_with(rand::random::<u32>)
+            .find(|id| !sessions.contains_key(id))
+            .unwrap_or_default();
+        sessions.insert(id, tx);
+
+        Self { id, requests }
+    }
+
+    /// Returns true if the session is still live.
+    fn is_live(id: u32) -> bool {
+        let sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.contains_key(&id)
+    }
+
+    /// Pass a component request on to the session, if it is still live.
+    fn forward(id: u32, req: ComponentRequest) {
+        let sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(tx) = sessions.get(&id) {
+            let _ = tx.send(req);
+        }
+    }
+
+    /// Wait for the next component request of the session.
+    async fn next(&mut self) -> AnyResult<ComponentRequest> {
+        self.requests
+            .recv()
+            .await
+            .context("Reaction-roles setup session closed")
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let mut sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.remove(&self.id);
+    }
+}
+
 /// Command: Manage reaction-roles.
 pub struct Roles;
 
@@ -33,6 +92,7 @@ impl Roles {
             .attach(Self::classic)
             .attach(Self::slash)
             .permissions(Permissions::ADMINISTRATOR)
+            .category(Category::Admin)
             .option(
                 sub("setup", "Setup a new reaction-roles message.")
                     .attach(Setup::classic)
@@ -43,14 +103,90 @@ impl Roles {
                     .attach(Edit::classic)
                     .option(message("message", "Reaction-roles message to edit.").required()),
             )
+            .component(COMPONENT_PREFIX, Self::component)
     }
 
-    async fn classic(_ctx: Context, _req: ClassicRequest) -> CommandResponse {
-        todo!();
+    /// Respond to the setup controls and the role menu, and pass them on to the setup process.
+    async fn component(ctx: Context, req: ComponentRequest) -> CommandResponse {
+        let mut parts = req.value().split(':');
+        let action = parts.next().unwrap_or_default();
+        let user_id = parts.next().unwrap_or_default();
+        let session = parts.next().and_then(|s| s.parse().ok());
+
+        // Only the one who started the setup can use the controls.
+        let author_id = req.interaction.author_id();
+        if author_id.map_or(true, |id| id.to_string() != user_id) {
+            req.reply(
+                &ctx,
+                Reply::new()
+                    .content("Only the one who started the setup can use these controls.")
+                    .ephemeral(),
+            )
+            .await?;
+            return Ok(Response::none());
+        }
+
+        // The setup only lives as long as its process, which does not survive a restart.
+        let Some(session) = session.filter(|id| Session::is_live(*id)) else {
+            req.reply(
+                &ctx,
+                Reply::new()
+                    .content("This reaction-roles setup has expired.")
+                    .ephemeral(),
+            )
+            .await?;
+            return Ok(Response::none());
+        };
+
+        match action {
+            "select" => {
+                let resp = InteractionResponse {
+                    kind: InteractionResponseType::DeferredUpdateMessage,
+                    data: Some(InteractionResponseData::default()),
+                };
+
+                // Acknowledge the selection and delete the role menu.
+                ctx.interaction()
+                    .create_response(req.interaction.id, &req.interaction.token, &resp)
+                    .await?;
+                req.clear(&ctx).await?;
+            },
+            "done" => {
+                let content = format!(
+                    "Done; You can use `{prefix}bot edit` command to edit the message content, or \
+                     `{prefix}roles edit` command to edit the role mappings.",
+                    prefix = ctx.config.classic_prefix(req.interaction.guild_id)?
+                );
+                req.reply(&ctx, Reply::new().content(content).ephemeral())
+                    .await?;
+            },
+            "cancel" => {
+                req.reply(
+                    &ctx,
+                    Reply::new()
+                        .content("Reaction-roles setup canceled.")
+                        .ephemeral(),
+                )
+                .await?;
+            },
+            _ => {
+                return Err(
+                    anyhow::anyhow!("Invalid roles component id '{}'", req.data.custom_id).into(),
+                )
+            },
+        }
+
+        Session::forward(session, req);
+
+        Ok(Response::none())
+    }
+
+    async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
+        req.reply_usage(&ctx).await
     }
 
-    async fn slash(_ctx: Context, _req: SlashRequest) -> CommandResponse {
-        todo!();
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        req.reply_usage(&ctx).await
     }
 }
 
@@ -227,8 +363,8 @@ async fn roles_setup_process(
         If any role is not displayed in the list, it may be too stronk for the bot.
     "};
 
-    let interaction = ctx.interaction();
     let mut mappings = preset.unwrap_or_default();
+    let mut session = Session::start();
 
     // Initial content of the controller message.
     let content = {
@@ -241,21 +377,14 @@ async fn roles_setup_process(
         .http
         .create_message(channel_id)
         .content(&content)?
-        .components(&controller_components(true))?
+        .components(&controller_components(author_id, session.id, true))?
         .send()
         .await?;
 
     // Add any previous reactions if this is an edit.
     add_reactions_to_message(ctx, &mappings, &controller).await?;
 
-    let controller_mci = loop {
-        // Future that waits for controller button press.
-        let controller_fut = ctx
-            .standby
-            .wait_for_component(controller.id, move |event: &Interaction| {
-                event.author_id() == Some(author_id)
-            });
-
+    let controller_req = loop {
         // Future that waits for a reaction add or remove.
         let reaction_fut = {
             let id = controller.id;
@@ -276,7 +405,7 @@ async fn roles_setup_process(
         let event = tokio::select! {
             biased;
             event = reaction_fut => event?, // Proceed with the reaction event.
-            mci = controller_fut => break mci?, // Exit loop with button interaction.
+            req = session.next() => break req?, // Exit loop with button interaction.
         };
 
         match event {
@@ -291,10 +420,11 @@ async fn roles_setup_process(
                     continue;
                 }
 
-                let components = dropdown_components(ctx, guild_id, &added.emoji).await?;
+                let components =
+                    dropdown_components(ctx, guild_id, author_id, session.id, &added.emoji).await?;
 
                 // Gray out controller buttons.
-                update_controller(ctx, &mut controller, None, false).await?;
+                update_controller(ctx, &mut controller, author_id, session.id, None, false).await?;
 
                 // Create dropdown list interaction.
                 let dropdown = ctx
@@ -305,31 +435,11 @@ async fn roles_setup_process(
                     .await?;
 
                 // Wait for user to select an option.
-                let list_mci = ctx
-                    .standby
-                    .wait_for_component(dropdown.id, move |event: &Interaction| {
-                        event.author_id() == Some(author_id)
-                    })
-                    .await?;
-
-                let resp = InteractionResponse {
-                    kind: InteractionResponseType::DeferredUpdateMessage,
-                    data: Some(InteractionResponseData::default()),
-                };
-
-                // Acknowledge the interaction.
-                interaction
-                    .create_response(list_mci.id, &list_mci.token, &resp)
-                    .await?;
-
-                // Delete the dropdown message.
-                interaction.delete_response(&list_mci.token).await?;
-
-                let choice = match list_mci.data {
-                    Some(InteractionData::MessageComponent(mut data)) => {
-                        data.values.pop().unwrap_or_default()
-                    },
-                    _ => {
+                // The component handler acknowledges it and deletes the dropdown message.
+                let list_req = session.next().await?;
+                let choice = match list_req.data.values.first() {
+                    Some(choice) => choice.to_owned(),
+                    None => {
                         error!("Received invalid interaction data in reaction-roles setup");
                         "cancel".to_string() // Because of this error, act as if it was canceled.
                     },
@@ -337,7 +447,8 @@ async fn roles_setup_process(
 
                 if choice == "cancel" {
                     // Canceling, re-enable controller buttons.
-                    update_controller(ctx, &mut controller, None, true).await?;
+                    update_controller(ctx, &mut controller, author_id, session.id, None, true)
+                        .await?;
 
                     // Remove canceled reaction.
                     let request_emoji = request_from_emoji(&added.emoji);
@@ -359,14 +470,23 @@ async fn roles_setup_process(
                         let content = format!("{info_text}\n{list}");
 
                         // Update the controller message and re-enable controller buttons.
-                        update_controller(ctx, &mut controller, Some(&content), true).await?;
+                        update_controller(
+                            ctx,
+                            &mut controller,
+                            author_id,
+                            session.id,
+                            Some(&content),
+                            true,
+                        )
+                        .await?;
                     },
                     Err(e) => {
                         // Error parsing the choice.
                         warn!("Could not parse role choice: {e}");
 
                         // Update the controller message and re-enable controller buttons.
-                        update_controller(ctx, &mut controller, None, true).await?;
+                        update_controller(ctx, &mut controller, author_id, session.id, None, true)
+                            .await?;
                     },
                 }
             },
@@ -382,7 +502,15 @@ async fn roles_setup_process(
                 let list = display_emoji_roles(ctx, guild_id, &mappings).await?;
                 let content = format!("{info_text}\n{list}");
 
-                update_controller(ctx, &mut controller, Some(&content), true).await?;
+                update_controller(
+                    ctx,
+                    &mut controller,
+                    author_id,
+                    session.id,
+                    Some(&content),
+                    true,
+                )
+                .await?;
 
                 let request_emoji = request_from_emoji(&removed.emoji);
 
@@ -397,47 +525,16 @@ async fn roles_setup_process(
         }
     };
 
-    let data = match controller_mci.data {
-        Some(InteractionData::MessageComponent(data)) => data,
-        _ => Err(anyhow::anyhow!(
-            "Received invalid interaction data in reaction-roles setup"
-        ))?,
-    };
+    // The component handler has responded to the button press already.
+    ctx.http
+        .delete_message(controller.channel_id, controller.id)
+        .await?;
 
     // If cancelled, no reaction-roles were added or all were removed.
-    if data.custom_id == "roles_cancel" || mappings.is_empty() {
-        // Delete the controller message.
-        ctx.http
-            .delete_message(controller.channel_id, controller.id)
-            .await?;
-
-        // Nothing more to be done here.
+    if controller_req.value().starts_with("cancel") || mappings.is_empty() {
         return Ok(None);
     }
 
-    let resp = InteractionResponse {
-        kind: InteractionResponseType::ChannelMessageWithSource,
-        data: Some(
-            InteractionResponseDataBuilder::new()
-                .flags(MessageFlags::EPHEMERAL)
-                .content(format!(
-                    "Done; You can use `{prefix}bot edit` command to edit the message content, or \
-                     `{prefix}roles edit` command to edit the role mappings.",
-                    prefix = ctx.config.classic_prefix(Some(guild_id))?
-                ))
-                .build(),
-        ),
-    };
-
-    interaction
-        .create_response(controller_mci.id, &controller_mci.token, &resp)
-        .await?;
-
-    // Delete the controller message.
-    ctx.http
-        .delete_message(controller.channel_id, controller.id)
-        .await?;
-
     Ok(Some(mappings))
 }
 
@@ -462,6 +559,8 @@ async fn add_reactions_to_message(
 async fn dropdown_components(
     ctx: &Context,
     guild_id: Id<GuildMarker>,
+    author_id: Id<UserMarker>,
+    session: u32,
     emoji: &ReactionType,
 ) -> AnyResult<Vec<Component>> {
     // Get all available roles. Try cache, otherwise fetch.
@@ -525,7 +624,7 @@ async fn dropdown_components(
     // Roles dropdown list.
     Ok(vec![Component::ActionRow(ActionRow {
         components: vec![Component::SelectMenu(SelectMenu {
-            custom_id: "role".to_string(),
+            custom_id: format!("{COMPONENT_PREFIX}select:{author_id}:{session}"),
             disabled: false,
             max_values: Some(1),
             min_values: Some(1),
@@ -535,12 +634,12 @@ async fn dropdown_components(
     })])
 }
 
-fn controller_components(enabled: bool) -> Vec<Component> {
+fn controller_components(author_id: Id<UserMarker>, session: u32, enabled: bool) -> Vec<Component> {
     vec![Component::ActionRow(ActionRow {
         components: vec![
             // Button to finish adding reactions.
             Component::Button(Button {
-                custom_id: Some("roles_done".to_string()),
+                custom_id: Some(format!("{COMPONENT_PREFIX}done:{author_id}:{session}")),
                 disabled: !enabled,
                 emoji: None,
                 label: Some("Done".to_string()),
@@ -549,7 +648,7 @@ fn controller_components(enabled: bool) -> Vec<Component> {
             }),
             // Button to cancel the process.
             Component::Button(Button {
-                custom_id: Some("roles_cancel".to_string()),
+                custom_id: Some(format!("{COMPONENT_PREFIX}cancel:{author_id}:{session}")),
                 disabled: !enabled,
                 emoji: None,
                 label: Some("Cancel".to_string()),
@@ -565,6 +664,8 @@ fn controller_components(enabled: bool) -> Vec<Component> {
 async fn update_controller(
     ctx: &Context,
     controller: &mut Message,
+    author_id: Id<UserMarker>,
+    session: u32,
     content: Option<&str>,
     enabled: bool,
 ) -> AnyResult<()> {
@@ -572,7 +673,7 @@ async fn update_controller(
         .http
         .update_message(controller.channel_id, controller.id)
         .content(content.or(Some(&controller.content)))?
-        .components(Some(&controller_components(enabled)))?
+        .components(Some(&controller_components(author_id, session, enabled)))?
         .send()
         .await?;
 
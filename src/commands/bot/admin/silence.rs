@@ -18,7 +18,9 @@ impl Mute {
             .attach(Self::classic)
             .attach(Self::slash)
             .attach(Self::user)
+            .no_defer(FunctionKind::User)
             .permissions(Permissions::ADMINISTRATOR)
+            .category(Category::Admin)
             .option(user("user", "Who to mute.").required())
             .option(integer("seconds", "Duration of the mute.").min(0))
     }
@@ -97,13 +99,21 @@ impl Mute {
     }
 
     async fn user(ctx: Context, req: UserRequest) -> CommandResponse {
-        req.clear(&ctx).await?; // Clear original beforehand.
+        use crate::commands::builder::*;
+
+        let submit = req
+            .modal(
+                &ctx,
+                modal("Mute").field(integer("seconds", "Duration of the mute.").min(0)),
+            )
+            .await?;
+        submit.clear(&ctx).await?; // Clear submit beforehand.
 
         Self::uber(
             ctx,
             req.interaction.guild_id,
             req.target_id,
-            None, // TODO: Create modal for duration input.
+            submit.args.integer("seconds").map(|i| i as u64).ok(),
         )
         .await
         .map(|_| Response::none())
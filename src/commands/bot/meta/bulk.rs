@@ -17,6 +17,7 @@ impl BulkDelete {
             .attach(Self::classic)
             .attach(Self::slash)
             .permissions(Permissions::ADMINISTRATOR)
+            .category(Category::Admin)
             .option(
                 integer("amount", "Number of messages to delete.")
                     .required()
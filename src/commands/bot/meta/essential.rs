@@ -20,22 +20,11 @@ impl Ping {
     }
 
     async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
-        ctx.http
-            .create_message(req.message.channel_id)
-            .reply(req.message.id)
-            .content("Pong!")?
-            .await?;
-
-        Ok(Response::none())
+        Ok(Response::reply(ctx, req, "Pong!"))
     }
 
     async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
-        ctx.interaction()
-            .create_followup(&req.interaction.token)
-            .content("Pong!")?
-            .await?;
-
-        Ok(Response::none())
+        Ok(Response::reply(ctx, req, "Pong!"))
     }
 }
 
@@ -77,13 +66,7 @@ impl About {
         }
         .uber(&ctx);
 
-        ctx.http
-            .create_message(req.message.channel_id)
-            .reply(req.message.id)
-            .content(&about_msg)?
-            .await?;
-
-        Ok(Response::none())
+        Ok(Response::reply(ctx, req, about_msg))
     }
 
     async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
@@ -94,85 +77,6 @@ impl About {
         }
         .uber(&ctx);
 
-        ctx.interaction()
-            .create_followup(&req.interaction.token)
-            .content(&about_msg)?
-            .await?;
-
-        Ok(Response::none())
-    }
-}
-
-/// Command: Help for using the bot, commands and usage.
-pub struct Help {
-    args: Args,
-    guild_id: Option<Id<GuildMarker>>,
-    channel_id: Option<Id<ChannelMarker>>,
-    message_id: Option<Id<MessageMarker>>,
-}
-
-impl Help {
-    pub fn command() -> impl Into<BaseCommand> {
-        use crate::commands::builder::*;
-
-        command("help", "List bot commands.")
-            .attach(Self::classic)
-            .attach(Self::slash)
-            .option(string("command", "Get help on a command."))
-            .dm()
-    }
-
-    fn uber(self, ctx: &Context) -> String {
-        if let Ok(value) = self.args.string("command") {
-            ctx.commands.get(&value).map_or_else(
-                || format!("Command `{value}` not found :|"),
-                |cmd| cmd.generate_help(),
-            )
-        } else {
-            formatdoc! {"
-                ```yaml
-                Prefix: '/' or '{prefix}'
-                Commands:
-                {commands}
-                ```",
-                prefix = ctx.config.classic_prefix(self.guild_id).unwrap_or_default(),
-                commands = ctx.commands
-            }
-        }
-    }
-
-    async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
-        let help_msg = Self {
-            args: req.args,
-            guild_id: req.message.guild_id,
-            channel_id: Some(req.message.channel_id),
-            message_id: Some(req.message.id),
-        }
-        .uber(&ctx);
-
-        ctx.http
-            .create_message(req.message.channel_id)
-            .reply(req.message.id)
-            .content(&help_msg)?
-            .await?;
-
-        Ok(Response::none())
-    }
-
-    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
-        let help_msg = Self {
-            args: req.args,
-            guild_id: req.interaction.guild_id,
-            channel_id: req.interaction.channel.as_ref().map(|c| c.id),
-            message_id: None,
-        }
-        .uber(&ctx);
-
-        ctx.interaction()
-            .create_followup(&req.interaction.token)
-            .content(&help_msg)?
-            .await?;
-
-        Ok(Response::none())
+        Ok(Response::reply(ctx, req, about_msg))
     }
 }
@@ -0,0 +1,393 @@
+use std::sync::Arc;
+
+use twilight_model::channel::message::component::{
+    ActionRow, Button, ButtonStyle, SelectMenu, SelectMenuOption,
+};
+use twilight_model::channel::message::{Component, Embed, MessageFlags};
+use twilight_model::http::interaction::{InteractionResponse, InteractionResponseType};
+use twilight_model::id::marker::UserMarker;
+use twilight_model::id::Id;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::commands::builder::Category;
+use crate::commands::handle::{Invoker, Lookup};
+use crate::commands::prelude::*;
+use crate::utils::prelude::*;
+
+/// Prefix of the help message component ids.
+const COMPONENT_PREFIX: &str = "help:";
+
+/// Number of commands listed on a category page.
+const PAGE_SIZE: usize = 10;
+
+/// Number of buttons in a row.
+const ROW_SIZE: usize = 5;
+
+/// Maximum number of subcommand buttons, leaving a row for the back button.
+const MAX_SUB_BUTTONS: usize = 4 * ROW_SIZE;
+
+/// Command: Help for using the bot, commands and usage.
+pub struct Help;
+
+impl Help {
+    pub fn command() -> impl Into<BaseCommand> {
+        use crate::commands::builder::*;
+
+        command("help", "List bot commands.")
+            .attach(Self::classic)
+            .attach(Self::slash)
+            .option(
+                string("command", "Get help on a command, such as `voice play`.")
+                    .autocomplete(Self::autocomplete)
+                    .rest(),
+            )
+            .component(COMPONENT_PREFIX, Self::component)
+            .dm()
+    }
+
+    async fn autocomplete(ctx: Context, req: AutocompleteRequest) -> AutocompleteResponse {
+        let input = req.input.trim().to_lowercase();
+
+        let Some(invoker) = Invoker::from_interaction(&req.interaction) else {
+            return Ok(Vec::new());
+        };
+        let usable = usable_commands(&ctx, &invoker)?;
+
+        Ok(ctx
+            .commands
+            .paths()
+            .into_iter()
+            .filter(|path| path.contains(&input))
+            .filter(|path| {
+                let name = path.split_whitespace().next();
+                usable.iter().any(|b| name == Some(b.command.name))
+            })
+            .map(|path| (path.clone(), ArgValue::String(path.into())))
+            .collect())
+    }
+
+    /// Returns the page requested in the arguments.
+    fn requested(args: &Args) -> Page {
+        match args.string("command") {
+            Ok(path) => Page::Command(path.to_lowercase()),
+            Err(_) => Page::Category(Category::default(), 0),
+        }
+    }
+
+    async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
+        let invoker = Invoker::from_message(&ctx, &req.message).await?;
+        let (embed, components) = render(&ctx, &invoker, &Self::requested(&req.args))?;
+
+        Ok(Response::reply(
+            ctx,
+            req,
+            Reply::new().embed(embed).components(components),
+        ))
+    }
+
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        let invoker =
+            Invoker::from_interaction(&req.interaction).context("Interaction has no user")?;
+        let (embed, components) = render(&ctx, &invoker, &Self::requested(&req.args))?;
+
+        Ok(Response::reply(
+            ctx,
+            req,
+            Reply::new().embed(embed).components(components),
+        ))
+    }
+
+    /// Switch the page of a help message.
+    async fn component(ctx: Context, req: ComponentRequest) -> CommandResponse {
+        let selected = req.data.values.first().map(String::as_str);
+        let Some((user_id, page)) = Page::parse(req.value(), selected) else {
+            return Err(
+                anyhow::anyhow!("Invalid help component id '{}'", req.data.custom_id).into(),
+            );
+        };
+
+        let invoker =
+            Invoker::from_interaction(&req.interaction).context("Interaction has no user")?;
+
+        // Only the one who asked for help can browse the message.
+        let data = if invoker.user_id == user_id {
+            let (embed, components) = render(&ctx, &invoker, &page)?;
+            InteractionResponse {
+                kind: InteractionResponseType::UpdateMessage,
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .embeds([embed])
+                        .components(components)
+                        .build(),
+                ),
+            }
+        } else {
+            InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(
+                    InteractionResponseDataBuilder::new()
+                        .content("Use `/help` to browse the commands yourself.")
+                        .flags(MessageFlags::EPHEMERAL)
+                        .build(),
+                ),
+            }
+        };
+
+        ctx.interaction()
+            .create_response(req.interaction.id, &req.interaction.token, &data)
+            .await?;
+
+        Ok(Response::none())
+    }
+}
+
+/// A page of a help message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Page {
+    /// Page of the commands in a category.
+    Category(Category, usize),
+    /// Help of a command, subcommand or group path, such as `voice play`.
+    Command(String),
+}
+
+impl Page {
+    /// Returns the component id for switching to this page, which only `user_id` can use.
+    fn custom_id(&self, user_id: Id<UserMarker>) -> String {
+        match self {
+            Self::Category(category, page) => {
+                format!("{COMPONENT_PREFIX}{user_id}:c:{}:{page}", category.name())
+            },
+            Self::Command(path) => format!("{COMPONENT_PREFIX}{user_id}:p:{path}"),
+        }
+    }
+
+    /// Parse the component id `value` after the prefix, returning the user that can use it and the page.
+    /// The category select menu gets the category from the `selected` value.
+    fn parse(value: &str, selected: Option<&str>) -> Option<(Id<UserMarker>, Self)> {
+        let mut parts = value.splitn(3, ':');
+        let user_id = parts.next()?.parse().ok()?;
+
+        let page = match (parts.next()?, parts.next()) {
+            ("s", None) => Self::Category(Category::from_name(selected?)?, 0),
+            ("c", Some(rest)) => {
+                let (name, page) = rest.split_once(':')?;
+                Self::Category(Category::from_name(name)?, page.parse().ok()?)
+            },
+            ("p", Some(path)) => Self::Command(path.to_string()),
+            _ => return None,
+        };
+
+        Some((user_id, page))
+    }
+}
+
+/// Returns the base commands that the invoker can use in the channel.
+fn usable_commands<'a>(
+    ctx: &'a Context,
+    invoker: &Invoker<'_>,
+) -> CommandResult<Vec<&'a Arc<BaseCommand>>> {
+    let mut usable = Vec::new();
+    for base in ctx.commands.inner().values() {
+        if invoker.can_use(ctx, base)? {
+            usable.push(base);
+        }
+    }
+
+    Ok(usable)
+}
+
+/// Render a help page with its navigation components, listing only the commands that the invoker can use.
+fn render(
+    ctx: &Context,
+    invoker: &Invoker<'_>,
+    page: &Page,
+) -> CommandResult<(Embed, Vec<Component>)> {
+    let usable = usable_commands(ctx, invoker)?;
+
+    match page {
+        Page::Category(category, page) => render_category(ctx, invoker, &usable, *category, *page),
+        Page::Command(path) => render_command(ctx, invoker, &usable, path),
+    }
+}
+
+/// Render a page of the commands in a category,
+/// or in the first category with any commands if there are none in `category`.
+fn render_category(
+    ctx: &Context,
+    invoker: &Invoker<'_>,
+    usable: &[&Arc<BaseCommand>],
+    category: Category,
+    page: usize,
+) -> CommandResult<(Embed, Vec<Component>)> {
+    let categories: Vec<_> = Category::ALL
+        .into_iter()
+        .filter(|c| usable.iter().any(|b| b.category == *c))
+        .collect();
+
+    let category = if categories.contains(&category) {
+        category
+    } else {
+        categories.first().copied().unwrap_or_default()
+    };
+
+    let commands: Vec<_> = usable.iter().filter(|b| b.category == category).collect();
+    let pages = commands.len().div_ceil(PAGE_SIZE).max(1);
+    let page = page.min(pages - 1);
+
+    let list = commands
+        .iter()
+        .skip(page * PAGE_SIZE)
+        .take(PAGE_SIZE)
+        .map(|b| format!("`{}` {}", b.command.name, b.command.description))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let prefix = ctx
+        .config
+        .classic_prefix(invoker.guild_id)
+        .unwrap_or_default();
+
+    let embed = EmbedBuilder::new()
+        .title(format!("Help: {}", category.name()))
+        .description(format!(
+            "{}\n\n{list}\n\nUse `/help <command>` for more about a command.",
+            category.description()
+        ))
+        .footer(EmbedFooterBuilder::new(format!(
+            "Page {}/{pages} · Prefix: '/' or '{prefix}'",
+            page + 1
+        )))
+        .validate()?
+        .build();
+
+    let mut components = Vec::new();
+
+    // Category switcher.
+    if categories.len() > 1 {
+        components.push(Component::ActionRow(ActionRow {
+            components: vec![Component::SelectMenu(SelectMenu {
+                custom_id: format!("{COMPONENT_PREFIX}{}:s", invoker.user_id),
+                disabled: false,
+                max_values: Some(1),
+                min_values: Some(1),
+                options: categories
+                    .iter()
+                    .map(|c| SelectMenuOption {
+                        default: *c == category,
+                        description: Some(c.description().to_string()),
+                        emoji: None,
+                        label: c.name().to_string(),
+                        value: c.name().to_string(),
+                    })
+                    .collect(),
+                placeholder: Some("Select a category".to_string()),
+            })],
+        }));
+    }
+
+    // Page buttons.
+    if pages > 1 {
+        let to = |page| Page::Category(category, page).custom_id(invoker.user_id);
+        components.push(Component::ActionRow(ActionRow {
+            components: vec![
+                button(to(page.saturating_sub(1)), "Previous", page == 0),
+                button(to(page + 1), "Next", page + 1 >= pages),
+            ],
+        }));
+    }
+
+    Ok((embed, components))
+}
+
+/// Render the help of a command path, with buttons to the help of its subcommands.
+fn render_command(
+    ctx: &Context,
+    invoker: &Invoker<'_>,
+    usable: &[&Arc<BaseCommand>],
+    path: &str,
+) -> CommandResult<(Embed, Vec<Component>)> {
+    let path: Vec<_> = path.split_whitespace().collect();
+    let not_found =
+        || CommandError::NotFound(format!("Command '{}' does not exist", path.join(" ")));
+
+    let (name, subs) = path.split_first().ok_or_else(not_found)?;
+    let base = usable
+        .iter()
+        .find(|b| b.command.name == *name)
+        .ok_or_else(not_found)?;
+    let lookup = Lookup::find(&base.command, subs).ok_or_else(not_found)?;
+
+    let help = if subs.is_empty() {
+        base.generate_help()
+    } else {
+        format!("```yaml\n{}\n```", lookup.usage())
+    };
+
+    // Usage with the classic prefix, if the command can be used that way.
+    let (prefix, flags) = if base.command.has_classic() {
+        let prefix = ctx
+            .config
+            .classic_prefix(invoker.guild_id)
+            .unwrap_or_default()
+            .into_inner();
+        (
+            prefix,
+            "\nArguments can also be given by name, as `--name value`.",
+        )
+    } else {
+        ("/".to_string(), "")
+    };
+    let synopsis = lookup.synopsis(&prefix, &path).join("\n");
+
+    let embed = EmbedBuilder::new()
+        .title(format!("Help: {}", path.join(" ")))
+        .description(format!("{help}\n**Usage**\n```\n{synopsis}\n```{flags}"))
+        .footer(EmbedFooterBuilder::new(format!(
+            "Category: {}",
+            base.category.name()
+        )))
+        .validate()?
+        .build();
+
+    let subcommands: Vec<_> = lookup
+        .children()
+        .iter()
+        .take(MAX_SUB_BUTTONS)
+        .map(|t| {
+            let page = Page::Command(format!("{} {}", path.join(" "), t.name()));
+            button(page.custom_id(invoker.user_id), t.name(), false)
+        })
+        .collect();
+
+    // Back to the parent command, or to the category of a base command.
+    let parent = match path.split_last() {
+        Some((_, parents)) if !parents.is_empty() => Page::Command(parents.join(" ")),
+        _ => Page::Category(base.category, 0),
+    };
+
+    let components = subcommands
+        .chunks(ROW_SIZE)
+        .map(|row| row.to_vec())
+        .chain([vec![button(
+            parent.custom_id(invoker.user_id),
+            "Back",
+            false,
+        )]])
+        .map(|components| Component::ActionRow(ActionRow { components }))
+        .collect();
+
+    Ok((embed, components))
+}
+
+/// Create a navigation button.
+fn button(custom_id: String, label: &str, disabled: bool) -> Component {
+    Component::Button(Button {
+        custom_id: Some(custom_id),
+        disabled,
+        emoji: None,
+        label: Some(label.to_string()),
+        style: ButtonStyle::Secondary,
+        url: None,
+    })
+}
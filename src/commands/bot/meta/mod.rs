@@ -1,4 +1,5 @@
 pub mod essential;
+pub mod help;
 
 #[cfg(feature = "bulk-delete")]
 pub mod bulk;
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use songbird::input::{Input, YoutubeDl};
 use songbird::tracks::Track;
@@ -24,6 +25,8 @@ impl Voice {
         command("voice", "Manage voice connection.")
             .attach(Self::classic)
             .attach(Self::slash)
+            .category(Category::Voice)
+            .cooldown(Cooldown::user(Duration::from_secs(5)).burst(3))
             .option(
                 sub("join", "Join the bot to a voice channel.")
                     .attach(Join::classic)
@@ -42,7 +45,11 @@ impl Voice {
                 sub("play", "Play a sound or music on voice (queued).")
                     .attach(Play::classic)
                     .attach(Play::slash)
-                    .option(string("url", "Youtube URL to play.").required()),
+                    .option(
+                        string("url", "Youtube URL to play.")
+                            .required()
+                            .autocomplete(Play::autocomplete),
+                    ),
             )
             .option(
                 sub("skip", "Go to the next track in queue.")
@@ -51,12 +58,12 @@ impl Voice {
             )
     }
 
-    async fn classic(_ctx: Context, _req: ClassicRequest) -> CommandResponse {
-        todo!();
+    async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
+        req.reply_usage(&ctx).await
     }
 
-    async fn slash(_ctx: Context, _req: SlashRequest) -> CommandResponse {
-        todo!();
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        req.reply_usage(&ctx).await
     }
 }
 
@@ -226,6 +233,37 @@ impl Leave {
 struct Play;
 
 impl Play {
+    /// Maximum length of a choice name.
+    const MAX_CHOICE_NAME: usize = 100;
+
+    /// Suggest the tracks in the queue by name, to play them again.
+    async fn autocomplete(ctx: Context, req: AutocompleteRequest) -> AutocompleteResponse {
+        let Some(call) = req.interaction.guild_id.and_then(|id| ctx.voice.get(id)) else {
+            return Ok(Vec::new());
+        };
+
+        let input = req.input.trim().to_lowercase();
+        let queue = call.lock().await.queue().current_queue();
+        let mut choices: Vec<(String, ArgValue)> = Vec::new();
+
+        for handle in queue {
+            let typemap = handle.typemap().read().await;
+            let Some(meta) = typemap.get::<Meta>() else {
+                continue;
+            };
+
+            let name: String = format!("{} by {}", meta.track, meta.artist)
+                .chars()
+                .take(Self::MAX_CHOICE_NAME)
+                .collect();
+            if name.to_lowercase().contains(&input) && !choices.iter().any(|(n, _)| *n == name) {
+                choices.push((name, ArgValue::String(meta.url.as_str().into())));
+            }
+        }
+
+        Ok(choices)
+    }
+
     async fn uber(
         ctx: &Context,
         args: &Args,
@@ -250,7 +288,8 @@ impl Play {
 
         let url = args.string("url")?;
         let client = reqwest::Client::new();
-        let mut input = Input::from(YoutubeDl::new(client, url.into_string()));
+        let url = url.into_string();
+        let mut input = Input::from(YoutubeDl::new(client, url.clone()));
         let meta = input.aux_metadata().await;
         let track = Track::new(input).volume(0.5);
 
@@ -279,7 +318,7 @@ impl Play {
                     .typemap()
                     .write()
                     .await
-                    .insert::<Meta>(Meta { track, artist });
+                    .insert::<Meta>(Meta { track, artist, url });
                 content
             },
             Err(e) => {
@@ -302,13 +341,7 @@ impl Play {
         )
         .await
         {
-            Ok(Some(content)) => {
-                ctx.http
-                    .create_message(req.message.channel_id)
-                    .content(&content)?
-                    .await?;
-                Ok(Response::none())
-            },
+            Ok(Some(content)) => Ok(Response::reply(ctx, req, content)),
             Ok(None) => Ok(Response::none()),
             Err(e) => Err(e.into()),
         }
@@ -328,13 +361,7 @@ impl Play {
         )
         .await
         {
-            Ok(Some(content)) => {
-                ctx.interaction()
-                    .create_followup(&req.interaction.token)
-                    .content(&content)?
-                    .await?;
-                Ok(Response::none())
-            },
+            Ok(Some(content)) => Ok(Response::reply(ctx, req, content)),
             Ok(None) => Ok(Response::clear(ctx, req)),
             Err(e) => Err(e.into()),
         }
@@ -369,13 +396,7 @@ impl Skip {
 
     async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
         match Self::uber(&ctx, req.message.guild_id.ok_or(CommandError::Disabled)?).await {
-            Ok(Some(content)) => {
-                ctx.http
-                    .create_message(req.message.channel_id)
-                    .content(&content)?
-                    .await?;
-                Ok(Response::none())
-            },
+            Ok(Some(content)) => Ok(Response::reply(ctx, req, content)),
             Ok(None) => Ok(Response::none()),
             Err(e) => Err(e.into()),
         }
@@ -388,13 +409,7 @@ impl Skip {
         )
         .await
         {
-            Ok(Some(content)) => {
-                ctx.interaction()
-                    .create_followup(&req.interaction.token)
-                    .content(&content)?
-                    .await?;
-                Ok(Response::none())
-            },
+            Ok(Some(content)) => Ok(Response::reply(ctx, req, content)),
             Ok(None) => Ok(Response::clear(ctx, req)),
             Err(e) => Err(e.into()),
         }
@@ -415,6 +430,7 @@ fn track_message(playing: bool, track: &str, artist: &str) -> String {
 struct Meta {
     track: String,
     artist: String,
+    url: String,
 }
 
 impl TypeMapKey for Meta {
@@ -33,6 +33,7 @@ impl Command {
 ```
 */
 
+use crate::commands::middleware::{AuditLog, Timing};
 use crate::commands::{Commands, CommandsBuilder};
 use crate::utils::prelude::*;
 
@@ -59,7 +60,7 @@ pub fn create_commands() -> AnyResult<Commands> {
     commands
         .bind(meta::essential::Ping::command())
         .bind(meta::essential::About::command())
-        .bind(meta::essential::Help::command());
+        .bind(meta::help::Help::command());
 
     #[cfg(feature = "voice")]
     commands.bind(meta::voice::Voice::command());
@@ -79,7 +80,9 @@ pub fn create_commands() -> AnyResult<Commands> {
     // Moderation functionality.
     #[cfg(feature = "admin")]
     commands
+        .bind(admin::alias::Alias::command())
         .bind(admin::bot::Bot::command())
+        .bind(admin::manage::ManageCommands::command())
         .bind(admin::roles::Roles::command())
         .bind(admin::silence::Mute::command());
 
@@ -87,6 +90,9 @@ pub fn create_commands() -> AnyResult<Commands> {
     #[cfg(feature = "owner")]
     commands.bind(owner::Shutdown::command());
 
+    // Logging around every command.
+    commands.wrap(AuditLog).wrap(Timing::default());
+
     commands
         .validate()
         .context("Failed to validate commands list")?;
@@ -11,24 +11,13 @@ impl Shutdown {
 
         command("shutdown", "Shutdown the bot.")
             .attach(Self::classic)
+            .attach(Self::slash)
+            .owner_only()
+            .category(Category::Owner)
             .dm()
     }
 
     async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
-        // Owner check (not done by command handling).
-        let sender_id = req.message.author.id;
-        let ok = if let Some(owner) = &ctx.application.owner {
-            owner.id == sender_id
-        } else if let Some(team) = &ctx.application.team {
-            team.members.iter().any(|m| m.user.id == sender_id)
-        } else {
-            false
-        };
-
-        if !ok {
-            return Ok(Response::none());
-        }
-
         info!("Shutting down by chat command");
 
         ctx.http
@@ -43,4 +32,18 @@ impl Shutdown {
 
         Ok(Response::none())
     }
+
+    async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
+        info!("Shutting down by slash command");
+
+        ctx.interaction()
+            .create_followup(&req.interaction.token)
+            .content("Shutting down...")?
+            .await?;
+
+        // Send a shutdown signal to the bot.
+        ctx.events_tx.send(BotEvent::Shutdown)?;
+
+        Ok(Response::none())
+    }
 }
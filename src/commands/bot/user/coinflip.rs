@@ -9,7 +9,10 @@ impl Coinflip {
     pub fn command() -> impl Into<BaseCommand> {
         use crate::commands::builder::*;
 
-        command("coinflip", "Flip a coin.").attach(Self::slash).dm()
+        command("coinflip", "Flip a coin.")
+            .attach(Self::slash)
+            .category(Category::User)
+            .dm()
     }
 
     async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
@@ -1,9 +1,34 @@
 use chrono::NaiveTime;
 use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder};
 
+use crate::commands::builder::{integer, number};
 use crate::commands::prelude::*;
 use crate::utils::prelude::*;
 
+from_args! {
+    /// Arguments of the fuel command.
+    struct FuelArgs {
+        stint: i64 = integer("stint-minutes", "Length of the race or stint in minutes.")
+            .required()
+            .min(1),
+        minutes: i64 = integer("lap-minutes", "Lap time minutes.")
+            .required()
+            .min(0)
+            .max(30),
+        seconds: f64 = number(
+            "lap-seconds",
+            "Lap time seconds (and optionally milliseconds as decimal).",
+        )
+        .required()
+        .min(0.0)
+        .max(59.9999),
+        consumption: f64 = number("consumption", "Fuel consumption in litres per lap.")
+            .required()
+            .min(0.1)
+            .max(100.0),
+    }
+}
+
 /// Command: Calculate fuel required.
 pub struct Fuel;
 
@@ -13,40 +38,18 @@ impl Fuel {
 
         command("fuel", "Calculate race fuel required.")
             .attach(Self::slash)
-            .option(
-                integer("stint-minutes", "Length of the race or stint in minutes.")
-                    .required()
-                    .min(1),
-            )
-            .option(
-                integer("lap-minutes", "Lap time minutes.")
-                    .required()
-                    .min(0)
-                    .max(30),
-            )
-            .option(
-                number(
-                    "lap-seconds",
-                    "Lap time seconds (and optionally milliseconds as decimal).",
-                )
-                .required()
-                .min(0.0)
-                .max(59.9999),
-            )
-            .option(
-                number("consumption", "Fuel consumption in litres per lap.")
-                    .required()
-                    .min(0.1)
-                    .max(100.0),
-            )
+            .category(Category::User)
+            .options_from::<FuelArgs>()
             .dm()
     }
 
     async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
-        let stint = req.args.integer("stint-minutes")?;
-        let minutes = req.args.integer("lap-minutes")?;
-        let seconds = req.args.number("lap-seconds")?;
-        let consumption = req.args.number("consumption")?;
+        let FuelArgs {
+            stint,
+            minutes,
+            seconds,
+            consumption,
+        } = req.args.extract()?;
 
         let length_in_seconds = (stint * 60) as f64;
         let laptime_in_seconds = (minutes * 60) as f64 + seconds;
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use reqwest;
 
 use crate::commands::prelude::*;
@@ -20,7 +22,11 @@ impl Joke {
     pub fn command() -> impl Into<BaseCommand> {
         use crate::commands::builder::*;
 
-        command("joke", "Send a bad joke.").attach(Self::slash).dm()
+        command("joke", "Send a bad joke.")
+            .attach(Self::slash)
+            .category(Category::User)
+            .cooldown(Cooldown::user(Duration::from_secs(10)).burst(3))
+            .dm()
     }
 
     async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
@@ -4,6 +4,7 @@ use twilight_mention::Mention;
 use twilight_model::channel::message::Embed;
 use twilight_util::builder::embed::{self, EmbedFieldBuilder, EmbedFooterBuilder};
 
+use crate::commands::builder::string;
 use crate::commands::prelude::*;
 use crate::utils::prelude::*;
 
@@ -37,6 +38,29 @@ const TIMEZONES: [(&str, &str); 24] = [
     ("UTC+12", "+12"),
 ];
 
+from_args! {
+    /// Arguments of the time command.
+    struct TimeArgs {
+        expression: Option<String> = string("expression", "Time expression to evaluate."),
+        timezone: Option<String> = string(
+            "timezone",
+            "Your timezone offset (ignored if tz in expression).",
+        )
+        .autocomplete(timezones),
+    }
+}
+
+/// Suggests the timezones whose names match the typed input.
+async fn timezones(_ctx: Context, req: AutocompleteRequest) -> AutocompleteResponse {
+    let input = req.input.trim().to_lowercase();
+
+    Ok(TIMEZONES
+        .into_iter()
+        .filter(|(name, value)| name.to_lowercase().contains(&input) || value.contains(&input))
+        .map(|(name, value)| (name.to_string(), ArgValue::String(value.into())))
+        .collect())
+}
+
 /// Command: Display a discord timestamp.
 pub struct Time;
 
@@ -47,14 +71,8 @@ impl Time {
         command("time", "Display a discord timestamp.")
             .attach(Self::classic)
             .attach(Self::slash)
-            .option(string("expression", "Time expression to evaluate."))
-            .option(
-                string(
-                    "timezone",
-                    "Your timezone offset (ignored if tz in expression).",
-                )
-                .choices(TIMEZONES),
-            )
+            .category(Category::User)
+            .options_from::<TimeArgs>()
             .dm()
             .help(indoc::formatdoc! {"
                 Format examples: https://github.com/waltzofpearls/dateparser#accepted-date-formats
@@ -69,13 +87,13 @@ impl Time {
             })
     }
 
-    async fn uber(args: Args) -> CommandResult<Embed> {
-        let expr = args.string("expression").unwrap_or_default();
+    async fn uber(args: TimeArgs) -> CommandResult<Embed> {
+        let expr = args.expression.unwrap_or_default();
 
         let now = args
-            .string("timezone")
-            .and_then(|val| Ok(timezone(&val)?))
-            .unwrap_or_else(|_| Utc::now().into());
+            .timezone
+            .and_then(|val| timezone(&val).ok())
+            .unwrap_or_else(|| Utc::now().into());
 
         let parsed = if expr.trim().is_empty() {
             now
@@ -99,26 +117,13 @@ impl Time {
     }
 
     async fn classic(ctx: Context, req: ClassicRequest) -> CommandResponse {
-        let embed = Self::uber(req.args).await?;
-
-        ctx.http
-            .create_message(req.message.channel_id)
-            .reply(req.message.id)
-            .embeds(&[embed])?
-            .await?;
-
-        Ok(Response::none())
+        let embed = Self::uber(req.args.extract()?).await?;
+        Ok(Response::reply(ctx, req, embed))
     }
 
     async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
-        let embed = Self::uber(req.args).await?;
-
-        ctx.interaction()
-            .update_response(&req.interaction.token)
-            .embeds(Some(&[embed]))?
-            .await?;
-
-        Ok(Response::none())
+        let embed = Self::uber(req.args.extract()?).await?;
+        Ok(Response::reply(ctx, req, embed))
     }
 }
 
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use twilight_mention::Mention;
 use twilight_util::builder::embed::{self, EmbedFieldBuilder, ImageSource};
 
@@ -15,30 +17,42 @@ impl UserInfo {
 
         command("userinfo", "Get information about a user.")
             .attach(Self::slash)
+            .category(Category::User)
             .option(user("user", "User to show information about."))
             .dm()
     }
 
     async fn slash(ctx: Context, req: SlashRequest) -> CommandResponse {
-        let Some(guild_id) = req.interaction.guild_id else {
-            return Err(CommandError::Disabled);
-        };
-
         // If no args provided, check own props
-        let user_id = match req.args.user("user") {
-            Ok(user) => user.id(),
-            _ => req
-                .interaction
-                .author_id()
-                .ok_or(CommandError::MissingArgs)?,
+        let (user, member) = match req.args.resolve_user(&ctx, "user").await {
+            Ok(user) => {
+                let member = req
+                    .data
+                    .resolved
+                    .as_ref()
+                    .and_then(|r| r.members.get(&user.id))
+                    .map(|m| (m.nick.clone(), m.avatar, m.roles.clone()));
+                (user, member)
+            },
+            Err(CommandError::MissingArgs) => {
+                let user = req.interaction.author().ok_or(CommandError::MissingArgs)?;
+                let member = req
+                    .interaction
+                    .member
+                    .as_ref()
+                    .map(|m| (m.nick.clone(), m.avatar, m.roles.clone()));
+                (Arc::new(user.clone()), member)
+            },
+            Err(e) => return Err(e),
         };
+        let (nick, member_avatar, roles) = member.unwrap_or_default();
+        let user_id = user.id;
 
-        let member = ctx.http.guild_member(guild_id, user_id).send().await?;
-        // Somewhat of a redundant call, but some data may be missing on `member.user`.
+        // Resolved users are missing the banner and accent color, which are only included when fetched.
         let user = ctx.http.user(user_id).send().await?;
 
         // If no avatar for the user, get the default one
-        let image_url = match member.avatar.or(user.avatar) {
+        let image_url = match member_avatar.or(user.avatar) {
             Some(avatar) => {
                 format!("https://cdn.discordapp.com/avatars/{user_id}/{avatar}.png?size=4096")
             },
@@ -57,12 +71,11 @@ impl UserInfo {
             ))?);
         }
 
-        if let Some(nick) = member.nick {
+        if let Some(nick) = nick {
             embed = embed.field(EmbedFieldBuilder::new("AKA", nick).inline());
         }
 
-        let roles: String = member
-            .roles
+        let roles: String = roles
             .into_iter()
             .map(|i| format!("{} ", i.mention()))
             .collect();
@@ -70,7 +83,7 @@ impl UserInfo {
         let roles = if roles.is_empty() { "-" } else { roles };
 
         let embed = embed
-            .title(user.name)
+            .title(&user.name)
             .color(user.accent_color.unwrap_or(0))
             .image(ImageSource::url(image_url)?)
             .field(EmbedFieldBuilder::new("Roles", roles).inline())
@@ -23,6 +23,13 @@
 //! fn mention("name", "description") -> ArgDesc
 //! ```
 //!
+//! Options can also be added from a typed arguments struct, see `arg::from_args!`.
+//!
+//! ### Modal dialog creation:
+//! ```text
+//! fn modal("title") -> ModalBuilder
+//! ```
+//!
 
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -32,14 +39,18 @@ use thiserror::Error;
 pub use twilight_model::channel::ChannelType;
 pub use twilight_model::guild::Permissions;
 
+use crate::commands::arg::FromArgs;
 use crate::commands::builder::twilight::{
     CommandValidationError, MessageCommand, SlashCommand, TwilightCommand, UserCommand,
 };
+pub use crate::commands::cooldown::{Cooldown, CooldownScope};
+pub use crate::commands::function::FunctionKind;
 use crate::commands::function::{
-    ClassicFunction, Function, FunctionKind, IntoFunction, MessageFunction, SlashFunction,
-    UserFunction,
+    Autocomplete, Callable, ClassicFunction, ComponentHandler, Function, IntoFunction,
+    MessageFunction, SlashFunction, UserFunction,
 };
-use crate::commands::ResponseFuture;
+use crate::commands::request::{AutocompleteRequest, ComponentRequest};
+use crate::commands::{AutocompleteFuture, ResponseFuture};
 use crate::utils::prelude::*;
 use crate::Context;
 
@@ -110,6 +121,11 @@ pub const fn mention(name: &'static str, description: &'static str) -> ArgDesc {
     ArgDesc::new(name, description, ArgKind::Mention)
 }
 
+/// Create a new modal dialog.
+pub fn modal(title: impl Into<String>) -> ModalBuilder {
+    ModalBuilder::new(title)
+}
+
 /// Helper macro to implement common methods for data builder.
 /// This assumes `data` type implements `Default`.
 macro_rules! impl_data_builder {
@@ -145,6 +161,23 @@ macro_rules! impl_data_builder {
     }
 }
 
+/// Helper macro to implement autocomplete method for data builder.
+/// This assumes `data` type has an `autocomplete` field.
+macro_rules! impl_autocomplete_builder {
+    ($vis:vis fn autocomplete(..)) => {
+        /// Set a function that suggests choices while typing.
+        /// Only applies to slash commands and cannot be used together with choices.
+        $vis fn autocomplete<F, Fut>(mut self, function: F) -> Self
+        where
+            F: Fn(Context, AutocompleteRequest) -> Fut + Send + Sync + 'static,
+            Fut: AutocompleteFuture + 'static,
+        {
+            self.inner_mut().autocomplete = Some(Autocomplete(function.into_shared()));
+            self
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct NumberOptionBuilder(ArgDesc);
 
@@ -154,6 +187,8 @@ impl NumberOptionBuilder {
         pub fn new(..) -> Self(Number(NumericalData<f64>))
     );
 
+    impl_autocomplete_builder!(pub fn autocomplete(..));
+
     /// Set minimum value.
     pub fn min(mut self, min: f64) -> Self {
         self.inner_mut().min = Some(min);
@@ -185,6 +220,8 @@ impl IntegerOptionBuilder {
         pub fn new(..) -> Self(Integer(NumericalData<i64>))
     );
 
+    impl_autocomplete_builder!(pub fn autocomplete(..));
+
     /// Set minimum value.
     pub fn min(mut self, min: i64) -> Self {
         self.inner_mut().min = Some(min);
@@ -216,6 +253,8 @@ impl StringOptionBuilder {
         pub fn new(..) -> Self(String(StringData))
     );
 
+    impl_autocomplete_builder!(pub fn autocomplete(..));
+
     /// Maximum allowed length. Must be at least `1` and at most `6000`.
     pub fn max_length(mut self, max: u16) -> Self {
         self.inner_mut().max_length = Some(max);
@@ -228,6 +267,13 @@ impl StringOptionBuilder {
         self
     }
 
+    /// Make the argument consume the rest of a classic command message verbatim,
+    /// including any whitespace and quotes. Must be the last argument.
+    pub fn rest(mut self) -> Self {
+        self.inner_mut().rest = true;
+        self
+    }
+
     /// Set string option choices as `(name, value)` pairs.
     pub fn choices<N, V>(mut self, choices: impl IntoIterator<Item = (N, V)>) -> Self
     where
@@ -265,6 +311,7 @@ pub struct NumericalData<T> {
     pub min: Option<T>,
     pub max: Option<T>,
     pub choices: Vec<(String, T)>,
+    pub autocomplete: Option<Autocomplete>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -272,6 +319,8 @@ pub struct StringData {
     pub max_length: Option<u16>,
     pub min_length: Option<u16>,
     pub choices: Vec<(String, String)>,
+    pub autocomplete: Option<Autocomplete>,
+    pub rest: bool,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -279,6 +328,23 @@ pub struct ChannelData {
     pub channel_types: Vec<ChannelType>,
 }
 
+/// Returns a short name of a channel type for usage syntax.
+pub const fn channel_type_name(kind: ChannelType) -> &'static str {
+    match kind {
+        ChannelType::GuildText => "text",
+        ChannelType::GuildVoice => "voice",
+        ChannelType::GuildCategory => "category",
+        ChannelType::GuildAnnouncement => "announcement",
+        ChannelType::GuildStageVoice => "stage",
+        ChannelType::GuildForum => "forum",
+        ChannelType::AnnouncementThread
+        | ChannelType::PublicThread
+        | ChannelType::PrivateThread => "thread",
+        ChannelType::Private | ChannelType::Group => "dm",
+        _ => "other",
+    }
+}
+
 #[derive(Debug, Clone, Display)]
 pub enum ArgKind {
     #[display(fmt = "bool")]
@@ -312,6 +378,59 @@ pub enum ArgKind {
     Mention,
 }
 
+impl ArgKind {
+    /// Returns the type and the accepted values of the argument, such as `int 1..100` or `a|b`.
+    pub fn synopsis(&self) -> String {
+        /// Choice values, or the type with the value range.
+        fn numerical<T: std::fmt::Display>(name: &str, data: &NumericalData<T>) -> String {
+            if !data.choices.is_empty() {
+                return data
+                    .choices
+                    .iter()
+                    .map(|(_, value)| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join("|");
+            }
+
+            let bound = |b: &Option<T>| b.as_ref().map_or_else(String::new, ToString::to_string);
+            match (&data.min, &data.max) {
+                (None, None) => name.to_string(),
+                (min, max) => format!("{name} {}..{}", bound(min), bound(max)),
+            }
+        }
+
+        match self {
+            Self::Number(data) => numerical("number", data),
+            Self::Integer(data) => numerical("int", data),
+            Self::String(data) if !data.choices.is_empty() => data
+                .choices
+                .iter()
+                .map(|(_, value)| value.as_str())
+                .collect::<Vec<_>>()
+                .join("|"),
+            Self::Channel(data) if !data.channel_types.is_empty() => format!(
+                "channel {}",
+                data.channel_types
+                    .iter()
+                    .map(|kind| channel_type_name(*kind))
+                    .collect::<Vec<_>>()
+                    .join("|")
+            ),
+            kind => kind.to_string(),
+        }
+    }
+
+    /// Returns the autocomplete hook of the argument, if any.
+    pub const fn autocomplete(&self) -> Option<&Autocomplete> {
+        match self {
+            Self::Number(NumericalData { autocomplete, .. })
+            | Self::Integer(NumericalData { autocomplete, .. })
+            | Self::String(StringData { autocomplete, .. }) => autocomplete.as_ref(),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ArgDesc {
     pub name: &'static str,
@@ -336,6 +455,119 @@ impl ArgDesc {
         self.required = true;
         self
     }
+
+    /// Returns true if the argument consumes the rest of a classic command message.
+    pub const fn is_rest(&self) -> bool {
+        matches!(self.kind, ArgKind::String(StringData { rest: true, .. }))
+    }
+
+    /// Returns the usage syntax of the argument, such as `<count:int 1..100>` or `[text:string...]`.
+    pub fn synopsis(&self) -> String {
+        let brackets = if self.required {
+            ['<', '>']
+        } else {
+            ['[', ']']
+        };
+        let rest = if self.is_rest() { "..." } else { "" };
+        format!(
+            "{}{}:{}{rest}{}",
+            brackets[0],
+            self.name,
+            self.kind.synopsis(),
+            brackets[1]
+        )
+    }
+}
+
+/// Modal dialog with text input fields.
+/// Submitted values are parsed as arguments by the field argument kinds.
+#[derive(Debug, Clone)]
+pub struct Modal {
+    pub title: String,
+    pub fields: Vec<ModalField>,
+}
+
+impl Modal {
+    /// Maximum number of fields in a modal.
+    pub const MAX_FIELDS: usize = 5;
+
+    /// Validate the modal.
+    pub fn validate(&self) -> AnyResult<()> {
+        anyhow::ensure!(
+            (1..=Self::MAX_FIELDS).contains(&self.fields.len()),
+            "Modal '{}' must have 1 to {} fields",
+            self.title,
+            Self::MAX_FIELDS
+        );
+
+        let mut seen = HashSet::new();
+        for field in self.fields.iter() {
+            anyhow::ensure!(
+                seen.insert(field.arg.name),
+                "Duplicate field '{}' in modal '{}'",
+                field.arg.name,
+                self.title
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Text input field of a modal dialog.
+#[derive(Debug, Clone)]
+pub struct ModalField {
+    /// Argument that the input is parsed as, the description is used as the label.
+    pub arg: ArgDesc,
+    /// If the input should be a multi-line text area.
+    pub paragraph: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModalBuilder(Modal);
+
+impl ModalBuilder {
+    /// Create a new modal builder.
+    pub fn new(title: impl Into<String>) -> Self {
+        Self(Modal {
+            title: title.into(),
+            fields: Vec::new(),
+        })
+    }
+
+    /// Add a single-line text input field.
+    pub fn field(mut self, arg: impl Into<ArgDesc>) -> Self {
+        self.0.fields.push(ModalField {
+            arg: arg.into(),
+            paragraph: false,
+        });
+        self
+    }
+
+    /// Add a multi-line text input field.
+    pub fn paragraph(mut self, arg: impl Into<ArgDesc>) -> Self {
+        self.0.fields.push(ModalField {
+            arg: arg.into(),
+            paragraph: true,
+        });
+        self
+    }
+
+    /// Validate the modal.
+    pub fn validate(&self) -> AnyResult<()> {
+        self.0.validate()
+    }
+
+    /// Finalize the modal.
+    pub fn build(self) -> Modal {
+        self.0
+    }
+}
+
+impl From<ModalBuilder> for Modal {
+    fn from(value: ModalBuilder) -> Self {
+        value.build()
+    }
 }
 
 /// This error type contains a collection of missing function errors found in a command.
@@ -358,6 +590,71 @@ impl std::fmt::Display for MissingFunctionsError {
     }
 }
 
+/// How multiple functions attached for the same request kind are executed.
+/// All functions are run, and their responses are run after each function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Execution {
+    /// Functions are run at the same time.
+    #[default]
+    Concurrent,
+    /// Functions are run one after another, in the order they were attached.
+    Sequential,
+}
+
+/// Category of a base command, used to group commands in help.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    /// Generic bot commands.
+    #[default]
+    Meta,
+    /// Normal user commands.
+    User,
+    /// Moderation commands.
+    Admin,
+    /// Bot owner only commands.
+    Owner,
+    /// Voice connection commands.
+    Voice,
+}
+
+impl Category {
+    /// All categories in display order.
+    pub const ALL: [Self; 5] = [
+        Self::Meta,
+        Self::User,
+        Self::Admin,
+        Self::Owner,
+        Self::Voice,
+    ];
+
+    /// Returns the name of the category.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Meta => "meta",
+            Self::User => "user",
+            Self::Admin => "admin",
+            Self::Owner => "owner",
+            Self::Voice => "voice",
+        }
+    }
+
+    /// Returns a short description of the category.
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Meta => "Generic bot commands.",
+            Self::User => "Utility and fun commands.",
+            Self::Admin => "Moderation and bot management commands.",
+            Self::Owner => "Bot owner only commands.",
+            Self::Voice => "Voice connection commands.",
+        }
+    }
+
+    /// Find a category by its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.name() == name)
+    }
+}
+
 /// Base command type, contains meta information with the command itself.
 #[derive(Debug, Clone)]
 pub struct BaseCommand {
@@ -373,9 +670,27 @@ pub struct BaseCommand {
     /// - `Some(Permissions::all())`: Administrator,
     /// - `Some(perms)`: User must satisfy all contained perms,
     pub member_permissions: Option<Permissions>,
+    /// If the command can only be used by the bot owner.
+    pub owner_only: bool,
+    /// Category for grouping the command in help.
+    pub category: Category,
+    /// Message component handlers, matched by `custom_id` prefix.
+    pub components: Vec<ComponentHandler>,
+    /// Kinds of functions whose interactions are not acknowledged before calling them,
+    /// so that they can respond with a modal.
+    pub no_defer: Vec<FunctionKind>,
+    /// Limit for how often the command, or each of its subcommands, can be used.
+    pub cooldown: Option<Cooldown>,
+    /// How multiple attached functions of the same kind are executed.
+    pub execution: Execution,
 }
 
 impl BaseCommand {
+    /// Returns true if interactions of the `kind` of functions are acknowledged before calling them.
+    pub fn defers(&self, kind: FunctionKind) -> bool {
+        !self.no_defer.contains(&kind)
+    }
+
     /// Generate commands to be integrated to discord.
     pub fn twilight_commands(
         &self,
@@ -395,9 +710,37 @@ impl BaseCommand {
             })
     }
 
+    /// Returns paths of the command and all of its subcommands and groups, such as `["voice", "play"]`.
+    pub fn paths(&self) -> Vec<Vec<&'static str>> {
+        let name = self.command.name;
+        let mut paths = vec![vec![name]];
+
+        for opt in self.command.options.iter() {
+            match opt {
+                CommandOption::Arg(_) => {},
+                CommandOption::Sub(s) => paths.push(vec![name, s.name]),
+                CommandOption::Group(g) => {
+                    paths.push(vec![name, g.name]);
+                    for s in g.subs.iter() {
+                        paths.push(vec![name, g.name, s.name]);
+                    }
+                },
+            }
+        }
+
+        paths
+    }
+
     /// Validate the command.
     pub fn validate(&self) -> AnyResult<()> {
         self.check_missing_functions()?;
+        self.check_rest_args()?;
+
+        anyhow::ensure!(
+            self.components.iter().all(|c| !c.prefix.is_empty()),
+            "Empty component prefix found in command '{}'",
+            self.command.name
+        );
 
         // HACK: Mostly waste of cpu cycles.
         self.twilight_commands()
@@ -491,6 +834,34 @@ impl BaseCommand {
             Err(MissingFunctionsError { errors })
         }
     }
+
+    /// Checks that arguments consuming the rest of the input are the last arguments.
+    fn check_rest_args(&self) -> AnyResult<()> {
+        fn check(func: &CommandFunction) -> AnyResult<()> {
+            let mut args = func.args().peekable();
+            while let Some(arg) = args.next() {
+                anyhow::ensure!(
+                    !arg.is_rest() || args.peek().is_none(),
+                    "Argument '{}' consumes the rest of the input, but is not the last argument \
+                     of command '{}'",
+                    arg.name,
+                    func.name
+                );
+            }
+
+            for opt in func.options.iter() {
+                match opt {
+                    CommandOption::Arg(_) => {},
+                    CommandOption::Sub(s) => check(s)?,
+                    CommandOption::Group(g) => g.subs.iter().try_for_each(check)?,
+                }
+            }
+
+            Ok(())
+        }
+
+        check(&self.command)
+    }
 }
 
 impl From<BaseCommandBuilder> for BaseCommand {
@@ -509,6 +880,12 @@ impl BaseCommandBuilder {
             help: String::new(),
             dm_enabled: false,
             member_permissions: None,
+            owner_only: false,
+            category: Category::Meta,
+            components: Vec::new(),
+            no_defer: Vec::new(),
+            cooldown: None,
+            execution: Execution::Concurrent,
         })
     }
 
@@ -530,6 +907,39 @@ impl BaseCommandBuilder {
         self
     }
 
+    /// Restrict the command to the bot owner (or owner team members).
+    /// Owner commands are hidden from others and registered only to the owner guild.
+    pub const fn owner_only(mut self) -> Self {
+        self.0.owner_only = true;
+        self
+    }
+
+    /// Set the category of the command, shown in help.
+    pub const fn category(mut self, category: Category) -> Self {
+        self.0.category = category;
+        self
+    }
+
+    /// Set a cooldown for using the command and each of its subcommands separately.
+    pub const fn cooldown(mut self, cooldown: Cooldown) -> Self {
+        self.0.cooldown = Some(cooldown);
+        self
+    }
+
+    /// Leave interactions of the `kind` of functions unacknowledged, so that they can respond with a modal.
+    /// Those functions must then respond to the interactions themselves.
+    pub fn no_defer(mut self, kind: FunctionKind) -> Self {
+        self.0.no_defer.push(kind);
+        self
+    }
+
+    /// Execute multiple attached functions of the same kind one after another,
+    /// in the order they were attached, instead of concurrently.
+    pub const fn sequential(mut self) -> Self {
+        self.0.execution = Execution::Sequential;
+        self
+    }
+
     // NOTE: Technically this should work with just `function: impl IntoFunction<R>` as parameter.
     // Though, without the additional bounds the compiler can sometimes generate "false" errors,
     // even if the problem is actually somewhere else. (Maybe related to incomplete features that are in use)
@@ -549,6 +959,32 @@ impl BaseCommandBuilder {
         self
     }
 
+    /// Add the options of a typed arguments struct to the command.
+    pub fn options_from<T: FromArgs>(mut self) -> Self {
+        self.0
+            .command
+            .options
+            .extend(T::options().into_iter().map(Into::into));
+        self
+    }
+
+    /// Add a message component handler to this base command.
+    /// The function gets called for every component interaction whose `custom_id`
+    /// starts with `prefix`, if no other handler has a longer matching prefix.
+    ///
+    /// Components that are awaited with standby should not use a registered prefix.
+    pub fn component<F, Fut>(mut self, prefix: &'static str, function: F) -> Self
+    where
+        F: Fn(Context, ComponentRequest) -> Fut + Send + Sync + 'static,
+        Fut: ResponseFuture + 'static,
+    {
+        self.0.components.push(ComponentHandler {
+            prefix,
+            function: Arc::new(function),
+        });
+        self
+    }
+
     /// Validate the command.
     pub fn validate(&self) -> AnyResult<()> {
         self.0.validate()
@@ -627,8 +1063,45 @@ impl CommandFunction {
         self.options.iter().filter_map(|o| o.arg())
     }
 
+    /// Generate usage syntax lines for the command and all of its subcommands,
+    /// each starting with `prefix`, such as `!voice play <url:string>` for prefix `!voice `.
+    /// Only classic subcommands are listed, unless the prefix is `/`.
+    pub fn synopsis(&self, prefix: &str) -> Vec<String> {
+        self.synopsis_with(prefix, prefix != "/")
+    }
+
+    /// Generate usage syntax lines, listing only classic subcommands if `classic` is true.
+    pub fn synopsis_with(&self, prefix: &str, classic: bool) -> Vec<String> {
+        let head = format!("{prefix}{}", self.name);
+        let mut lines = Vec::new();
+
+        // Commands with subcommands cannot be called with arguments.
+        if self.args().next().is_some() || self.options.is_empty() {
+            let args: Vec<_> = self.args().map(ArgDesc::synopsis).collect();
+            lines.push(
+                [head.clone()]
+                    .into_iter()
+                    .chain(args)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+        }
+
+        let sub_prefix = format!("{head} ");
+        for opt in self.options.iter() {
+            match opt {
+                CommandOption::Arg(_) => {},
+                CommandOption::Sub(s) if classic && !s.has_classic() => {},
+                CommandOption::Sub(s) => lines.extend(s.synopsis_with(&sub_prefix, classic)),
+                CommandOption::Group(g) => lines.extend(g.synopsis_with(&sub_prefix, classic)),
+            }
+        }
+
+        lines
+    }
+
     /// Generate usage help text.
-    fn generate_help(&self, indent: usize) -> String {
+    pub fn generate_help(&self, indent: usize) -> String {
         let mut opt_help = String::new();
         for opt in self.options.iter() {
             opt_help.push('\n');
@@ -682,6 +1155,14 @@ impl CommandFunctionBuilder {
         self
     }
 
+    /// Add the options of a typed arguments struct to the command.
+    pub fn options_from<T: FromArgs>(mut self) -> Self {
+        self.0
+            .options
+            .extend(T::options().into_iter().map(Into::into));
+        self
+    }
+
     /// Finalize the command.
     pub fn build(self) -> CommandFunction {
         self.0
@@ -699,6 +1180,33 @@ impl CommandGroup {
     pub fn to_options(&self) -> Vec<CommandOption> {
         self.subs.iter().cloned().map(CommandOption::Sub).collect()
     }
+
+    /// Generate usage syntax lines for all subcommands of the group, each starting with `prefix`.
+    /// Only classic subcommands are listed, unless the prefix is `/`.
+    pub fn synopsis(&self, prefix: &str) -> Vec<String> {
+        self.synopsis_with(prefix, prefix != "/")
+    }
+
+    /// Generate usage syntax lines, listing only classic subcommands if `classic` is true.
+    pub fn synopsis_with(&self, prefix: &str, classic: bool) -> Vec<String> {
+        let sub_prefix = format!("{prefix}{} ", self.name);
+        self.subs
+            .iter()
+            .filter(|s| !classic || s.has_classic())
+            .flat_map(|s| s.synopsis_with(&sub_prefix, classic))
+            .collect()
+    }
+
+    /// Generate usage help text.
+    pub fn generate_help(&self, indent: usize) -> String {
+        let mut sub_help = format!("{:<16} {}", self.name, self.description);
+        for sub in self.subs.iter() {
+            sub_help.push('\n');
+            sub_help.push_str(&"\t".repeat(indent + 1));
+            sub_help.push_str(&sub.generate_help(indent + 1));
+        }
+        sub_help
+    }
 }
 
 impl From<CommandGroupBuilder> for CommandGroup {
@@ -766,7 +1274,7 @@ impl CommandOption {
     }
 
     /// Generate usage help text.
-    fn generate_help(&self, indent: usize) -> String {
+    pub fn generate_help(&self, indent: usize) -> String {
         match self {
             Self::Arg(a) => {
                 let brackets = if a.required { ['<', '>'] } else { ['[', ']'] };
@@ -774,15 +1282,7 @@ impl CommandOption {
                 format!("{name:<16} {}", a.description)
             },
             Self::Sub(s) => s.generate_help(indent),
-            Self::Group(g) => {
-                let mut sub_help = format!("{:<16} {}", g.name, g.description);
-                for sub in g.subs.iter() {
-                    sub_help.push('\n');
-                    sub_help.push_str(&"\t".repeat(indent + 1));
-                    sub_help.push_str(&sub.generate_help(indent + 1));
-                }
-                sub_help
-            },
+            Self::Group(g) => g.generate_help(indent),
         }
     }
 }
@@ -811,6 +1311,30 @@ impl From<ChannelOptionBuilder> for CommandOption {
     }
 }
 
+impl From<NumberOptionBuilder> for ArgDesc {
+    fn from(value: NumberOptionBuilder) -> Self {
+        value.build()
+    }
+}
+
+impl From<IntegerOptionBuilder> for ArgDesc {
+    fn from(value: IntegerOptionBuilder) -> Self {
+        value.build()
+    }
+}
+
+impl From<StringOptionBuilder> for ArgDesc {
+    fn from(value: StringOptionBuilder) -> Self {
+        value.build()
+    }
+}
+
+impl From<ChannelOptionBuilder> for ArgDesc {
+    fn from(value: ChannelOptionBuilder) -> Self {
+        value.build()
+    }
+}
+
 impl From<ArgDesc> for CommandOption {
     fn from(value: ArgDesc) -> Self {
         Self::Arg(value)
@@ -959,7 +1483,8 @@ mod tests {
                                     .attach(mock::classic)
                                     .attach(mock::slash)
                                     .option(bool("ecba", "description").required())
-                                    .option(bool("ecbb", "description")),
+                                    .option(bool("ecbb", "description"))
+                                    .option(string("ecbc", "description").rest()),
                             ),
                     ),
             );
@@ -977,6 +1502,75 @@ mod tests {
             .for_each(|(e, c)| panic!("\n{c:#?}\n\n{e}"));
     }
 
+    #[test]
+    fn rest_arg_must_be_last() {
+        let cmd = command("f", "description")
+            .attach(mock::classic)
+            .option(string("fa", "description").rest())
+            .option(bool("fb", "description"))
+            .build();
+
+        assert!(cmd.validate().is_err());
+    }
+
+    #[test]
+    fn usage_synopsis() {
+        let cmd = command("fuel", "description")
+            .attach(mock::classic)
+            .option(integer("stint-minutes", "description").required().min(1))
+            .option(
+                integer("lap-minutes", "description")
+                    .required()
+                    .min(0)
+                    .max(30),
+            )
+            .option(number("consumption", "description"))
+            .build();
+
+        assert_eq!(
+            vec!["!fuel <stint-minutes:int 1..> <lap-minutes:int 0..30> [consumption:number]"],
+            cmd.command.synopsis("!")
+        );
+
+        let e = commands().iter().find(|c| c.command.name == "e").unwrap();
+        assert_eq!(
+            vec![
+                "/e ea",
+                "/e eb [eaa:24|42] [eab:24|42] [eac:bar] [ead:channel text] [eae:bool] [eaf:user] \
+                 [eag:role] [eah:message] [eai:mention] [eaj:attachment]",
+                "/e ec eca",
+                "/e ec ecb <ecba:bool> [ecbb:bool] [ecbc:string...]",
+            ],
+            e.command.synopsis("/")
+        );
+
+        // Slash only subcommands are not listed for classic commands.
+        assert_eq!(
+            vec![
+                "!e eb [eaa:24|42] [eab:24|42] [eac:bar] [ead:channel text] [eae:bool] [eaf:user] \
+                 [eag:role] [eah:message] [eai:mention] [eaj:attachment]",
+                "!e ec ecb <ecba:bool> [ecbb:bool] [ecbc:string...]",
+            ],
+            e.command.synopsis("!")
+        );
+    }
+
+    #[test]
+    fn command_paths() {
+        let e = commands().iter().find(|c| c.command.name == "e").unwrap();
+        assert_eq!(
+            vec![
+                vec!["e"],
+                vec!["e", "ea"],
+                vec!["e", "eb"],
+                vec!["e", "ec"],
+                vec!["e", "ec", "eca"],
+                vec!["e", "ec", "ecb"],
+            ],
+            e.paths()
+        );
+    }
+
     #[test]
     fn commands_help() {
         commands()
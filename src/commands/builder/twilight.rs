@@ -1,5 +1,8 @@
 use thiserror::Error;
 use twilight_model::application::command::{Command, CommandOption, CommandType};
+use twilight_model::channel::message::component::{ActionRow, TextInput, TextInputStyle};
+use twilight_model::channel::message::Component;
+use twilight_model::id::Id;
 use twilight_util::builder::command::*;
 
 use crate::commands::builder::BaseCommand;
@@ -25,6 +28,11 @@ trait Optional: Sized {
 
 impl<T> Optional for T {}
 
+/// Returns `None` if the list is empty, so that it can be left out of the builder.
+fn non_empty<T>(list: Vec<T>) -> Option<Vec<T>> {
+    (!list.is_empty()).then_some(list)
+}
+
 pub trait CommandBuilderExt<Output = Command>: Sized {
     fn build_checked(self) -> AnyResult<Output>;
 }
@@ -74,6 +82,15 @@ pub fn validate_command(cmd: &Command) -> Result<(), CommandValidationError> {
                 }
             }
 
+            // Discord rejects options that have both choices and autocomplete.
+            if opt.autocomplete == Some(true)
+                && opt.choices.as_ref().map_or(false, |c| !c.is_empty())
+            {
+                return Err(CommandValidationError::AutocompleteChoices(
+                    opt.name.to_owned(),
+                ));
+            }
+
             // Recursively check suboptions.
             if let Some(options) = &opt.options {
                 validate_options(options)?;
@@ -107,12 +124,44 @@ pub fn validate_command(cmd: &Command) -> Result<(), CommandValidationError> {
     Ok(())
 }
 
+/// Returns true if the commands are the same for registering purposes.
+/// Ignores ids and versions, and treats default values the same as missing values,
+/// as Discord leaves those out from the registered commands.
+pub fn commands_eq(a: &Command, b: &Command) -> bool {
+    fn normalize_option(opt: &mut CommandOption) {
+        opt.autocomplete = opt.autocomplete.filter(|b| *b);
+        opt.required = opt.required.filter(|b| *b);
+        opt.channel_types = opt.channel_types.take().and_then(non_empty);
+        opt.choices = opt.choices.take().and_then(non_empty);
+        opt.options = opt.options.take().and_then(non_empty);
+        opt.options.iter_mut().flatten().for_each(normalize_option);
+    }
+
+    fn normalize(cmd: &Command) -> Command {
+        let mut cmd = cmd.clone();
+        cmd.id = None;
+        cmd.application_id = None;
+        cmd.guild_id = None;
+        cmd.version = Id::new(1);
+        cmd.dm_permission = cmd.dm_permission.or(Some(true));
+        cmd.nsfw = cmd.nsfw.filter(|b| *b);
+        cmd.options.iter_mut().for_each(normalize_option);
+        cmd
+    }
+
+    normalize(a) == normalize(b)
+}
+
 #[derive(Debug, Error)]
 pub enum CommandValidationError {
     /// Multiple uses of same option name.
     #[error("Option names must be locally unique: {0}")]
     AmbiguousName(String),
 
+    /// Option with both choices and autocomplete.
+    #[error("Option '{0}' cannot have both choices and autocomplete")]
+    AutocompleteChoices(String),
+
     /// Options in GUI-based commands.
     #[error("GUI-based commands cannot have options")]
     GuiOptions,
@@ -255,21 +304,24 @@ impl From<super::ArgDesc> for CommandOption {
                 .build(),
             super::ArgKind::Number(d) => NumberBuilder::new(value.name, value.description)
                 .required(value.required)
-                .choices(d.choices)
+                .optional(non_empty(d.choices), |b, v| b.choices(v))
                 .optional(d.min, |b, v| b.min_value(v))
                 .optional(d.max, |b, v| b.max_value(v))
+                .autocomplete(d.autocomplete.is_some())
                 .build(),
             super::ArgKind::Integer(d) => IntegerBuilder::new(value.name, value.description)
                 .required(value.required)
-                .choices(d.choices)
+                .optional(non_empty(d.choices), |b, v| b.choices(v))
                 .optional(d.min, |b, v| b.min_value(v))
                 .optional(d.max, |b, v| b.max_value(v))
+                .autocomplete(d.autocomplete.is_some())
                 .build(),
             super::ArgKind::String(d) => StringBuilder::new(value.name, value.description)
                 .required(value.required)
-                .choices(d.choices)
+                .optional(non_empty(d.choices), |b, v| b.choices(v))
                 .optional(d.min_length, |b, v| b.min_length(v))
                 .optional(d.max_length, |b, v| b.max_length(v))
+                .autocomplete(d.autocomplete.is_some())
                 .build(),
             super::ArgKind::Channel(d) => ChannelBuilder::new(value.name, value.description)
                 .required(value.required)
@@ -295,3 +347,32 @@ impl From<super::ArgDesc> for CommandOption {
         }
     }
 }
+
+impl From<super::ModalField> for Component {
+    fn from(value: super::ModalField) -> Self {
+        let (min_length, max_length) = match value.arg.kind {
+            super::ArgKind::String(d) => (d.min_length, d.max_length),
+            _ => (None, None),
+        };
+
+        let style = if value.paragraph {
+            TextInputStyle::Paragraph
+        } else {
+            TextInputStyle::Short
+        };
+
+        // Each text input must be in its own action row.
+        Self::ActionRow(ActionRow {
+            components: vec![Self::TextInput(TextInput {
+                custom_id: value.arg.name.to_owned(),
+                label: value.arg.description.to_owned(),
+                max_length,
+                min_length,
+                placeholder: None,
+                required: Some(value.arg.required),
+                style,
+                value: None,
+            })],
+        })
+    }
+}
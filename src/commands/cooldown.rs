@@ -0,0 +1,190 @@
+//! Command cooldowns and rate limits.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use twilight_model::id::marker::{ChannelMarker, GuildMarker, UserMarker};
+use twilight_model::id::Id;
+
+/// Scope in which command uses are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CooldownScope {
+    /// Each user has their own cooldown.
+    User,
+    /// Each channel has its own cooldown.
+    Channel,
+    /// Each guild has its own cooldown, DM channels count as their own guilds.
+    Guild,
+    /// One cooldown shared by everyone.
+    Global,
+}
+
+impl CooldownScope {
+    /// Returns the id of the bucket that a command use belongs to.
+    pub fn bucket(
+        self,
+        user_id: Option<Id<UserMarker>>,
+        channel_id: Option<Id<ChannelMarker>>,
+        guild_id: Option<Id<GuildMarker>>,
+    ) -> u64 {
+        let channel = || channel_id.map_or(0, Id::get);
+        match self {
+            Self::User => user_id.map_or(0, Id::get),
+            Self::Channel => channel(),
+            Self::Guild => guild_id.map_or_else(channel, Id::get),
+            Self::Global => 0,
+        }
+    }
+}
+
+/// Command cooldown, allows `burst` uses within any `period` in a scope.
+#[derive(Debug, Clone, Copy)]
+pub struct Cooldown {
+    pub scope: CooldownScope,
+    pub period: Duration,
+    pub burst: u32,
+}
+
+impl Cooldown {
+    /// Create a new cooldown that allows one use per `period`.
+    pub const fn new(scope: CooldownScope, period: Duration) -> Self {
+        Self {
+            scope,
+            period,
+            burst: 1,
+        }
+    }
+
+    /// Create a new per user cooldown.
+    pub const fn user(period: Duration) -> Self {
+        Self::new(CooldownScope::User, period)
+    }
+
+    /// Create a new per channel cooldown.
+    pub const fn channel(period: Duration) -> Self {
+        Self::new(CooldownScope::Channel, period)
+    }
+
+    /// Create a new per guild cooldown.
+    pub const fn guild(period: Duration) -> Self {
+        Self::new(CooldownScope::Guild, period)
+    }
+
+    /// Create a new global cooldown.
+    pub const fn global(period: Duration) -> Self {
+        Self::new(CooldownScope::Global, period)
+    }
+
+    /// Set how many uses are allowed within the period. Must be at least `1`.
+    pub const fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+}
+
+/// Recent uses of a command in a single bucket.
+#[derive(Debug, Default)]
+struct Uses {
+    period: Duration,
+    times: VecDeque<Instant>,
+}
+
+/// Tracker for command uses, by the invoked command path and bucket.
+#[derive(Debug, Default)]
+pub struct Cooldowns {
+    uses: Mutex<HashMap<(String, u64), Uses>>,
+}
+
+impl Cooldowns {
+    /// Number of tracked buckets after which expired buckets are removed.
+    const CLEANUP_THRESHOLD: usize = 1024;
+
+    /// Register a use of a command path, such as `music play`, in a bucket.
+    /// Returns the time until the next use is allowed, if the command is on cooldown.
+    pub fn hit(&self, path: &str, cooldown: &Cooldown, bucket: u64) -> Result<(), Duration> {
+        self.hit_at(path, cooldown, bucket, Instant::now())
+    }
+
+    fn hit_at(
+        &self,
+        path: &str,
+        cooldown: &Cooldown,
+        bucket: u64,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let mut map = self.uses.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Forget buckets that have no uses within their period.
+        if map.len() >= Self::CLEANUP_THRESHOLD {
+            map.retain(|_, u| {
+                u.times
+                    .back()
+                    .map_or(false, |t| now.duration_since(*t) < u.period)
+            });
+        }
+
+        let uses = map.entry((path.to_string(), bucket)).or_default();
+        uses.period = cooldown.period;
+
+        // Drop uses that are outside of the sliding window.
+        while let Some(first) = uses.times.front() {
+            if now.duration_since(*first) < cooldown.period {
+                break;
+            }
+            uses.times.pop_front();
+        }
+
+        if uses.times.len() >= cooldown.burst.max(1) as usize {
+            let first = uses.times.front().copied().unwrap_or(now);
+            return Err(cooldown.period.saturating_sub(now.duration_since(first)));
+        }
+
+        uses.times.push_back(now);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_within_period() {
+        let cooldowns = Cooldowns::default();
+        let cooldown = Cooldown::user(Duration::from_secs(10)).burst(2);
+        let now = Instant::now();
+
+        assert_eq!(Ok(()), cooldowns.hit_at("cmd", &cooldown, 1, now));
+        assert_eq!(Ok(()), cooldowns.hit_at("cmd", &cooldown, 1, now));
+        assert_eq!(
+            Err(Duration::from_secs(7)),
+            cooldowns.hit_at("cmd", &cooldown, 1, now + Duration::from_secs(3))
+        );
+
+        // Other buckets and commands are not affected.
+        assert_eq!(Ok(()), cooldowns.hit_at("cmd", &cooldown, 2, now));
+        assert_eq!(Ok(()), cooldowns.hit_at("other", &cooldown, 1, now));
+
+        // Uses expire after the period.
+        assert_eq!(
+            Ok(()),
+            cooldowns.hit_at("cmd", &cooldown, 1, now + Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn separate_subcommands() {
+        let cooldowns = Cooldowns::default();
+        let cooldown = Cooldown::user(Duration::from_secs(10));
+        let now = Instant::now();
+
+        assert_eq!(Ok(()), cooldowns.hit_at("cmd sub", &cooldown, 1, now));
+        assert_eq!(Ok(()), cooldowns.hit_at("cmd other", &cooldown, 1, now));
+        assert_eq!(
+            Err(Duration::from_secs(10)),
+            cooldowns.hit_at("cmd sub", &cooldown, 1, now)
+        );
+    }
+}
@@ -1,8 +1,10 @@
+use std::pin::Pin;
 use std::sync::Arc;
 
 use derive_more::{IsVariant, Unwrap};
 
 use crate::commands::prelude::*;
+use crate::commands::{AutocompleteFuture, ResponseCallFuture};
 // use crate::utils::prelude::*;
 use crate::Context;
 
@@ -28,23 +30,27 @@ pub mod mock {
         println!("USER REQ: {req:#?}");
         Ok(Response::none())
     }
+
+    pub async fn component(_ctx: Context, req: ComponentRequest) -> CommandResponse {
+        tracing::trace!("COMPONENT REQ: {req:#?}");
+        Ok(Response::none())
+    }
 }
 
 macro_rules! function_trait {
-    ($request:ty => $var:path) => {
+    ($request:ty) => {
         impl<F, Fut> Callable<$request> for F
         where
             F: Fn(Context, $request) -> Fut + Send + Sync + 'static,
             Fut: ResponseFuture + 'static,
         {
-            fn call(&self, ctx: Context, req: $request) -> CallFuture {
-                use futures::TryFutureExt;
-                Box::pin((self)(ctx, req).and_then(|x| x))
+            fn call(&self, ctx: Context, req: $request) -> ResponseCallFuture {
+                Box::pin((self)(ctx, req))
             }
         }
 
         impl Callable<$request> for Arc<dyn Callable<$request>> {
-            fn call(&self, ctx: Context, req: $request) -> CallFuture {
+            fn call(&self, ctx: Context, req: $request) -> ResponseCallFuture {
                 (**self).call(ctx, req)
             }
 
@@ -52,6 +58,9 @@ macro_rules! function_trait {
                 self
             }
         }
+    };
+    ($request:ty => $var:path) => {
+        function_trait!($request);
 
         impl<T> IntoFunction<$request> for T
         where
@@ -72,14 +81,44 @@ function_trait!(SlashRequest => Function::Slash);
 function_trait!(MessageRequest => Function::Message);
 // Function that can handle GUI-based user command.
 function_trait!(UserRequest => Function::User);
+// Function that can handle message component interaction.
+function_trait!(ComponentRequest);
 
 pub type ClassicFunction = Arc<dyn Callable<ClassicRequest>>;
 pub type SlashFunction = Arc<dyn Callable<SlashRequest>>;
 pub type MessageFunction = Arc<dyn Callable<MessageRequest>>;
 pub type UserFunction = Arc<dyn Callable<UserRequest>>;
+pub type ComponentFunction = Arc<dyn Callable<ComponentRequest>>;
+
+/// Non-generic return type for async autocomplete functions.
+pub type AutocompleteCallFuture = Pin<Box<dyn AutocompleteFuture>>;
+
+pub type AutocompleteFunction = Arc<dyn Callable<AutocompleteRequest, AutocompleteCallFuture>>;
+
+// Function that can suggest choices for an argument.
+impl<F, Fut> Callable<AutocompleteRequest, AutocompleteCallFuture> for F
+where
+    F: Fn(Context, AutocompleteRequest) -> Fut + Send + Sync + 'static,
+    Fut: AutocompleteFuture + 'static,
+{
+    fn call(&self, ctx: Context, req: AutocompleteRequest) -> AutocompleteCallFuture {
+        Box::pin((self)(ctx, req))
+    }
+}
+
+impl Callable<AutocompleteRequest, AutocompleteCallFuture> for AutocompleteFunction {
+    fn call(&self, ctx: Context, req: AutocompleteRequest) -> AutocompleteCallFuture {
+        (**self).call(ctx, req)
+    }
+
+    fn into_shared(self) -> AutocompleteFunction {
+        self
+    }
+}
 
 /// Trait for functions that can be called with a generic request.
-pub trait Callable<R, O = CallFuture>: Send + Sync {
+/// Command functions return their response without completing it, so that middleware can change it.
+pub trait Callable<R, O = ResponseCallFuture>: Send + Sync {
     fn call(&self, ctx: Context, req: R) -> O;
     fn into_shared(self) -> Arc<dyn Callable<R, O>>
     where
@@ -126,6 +165,31 @@ impl std::fmt::Debug for Function {
     }
 }
 
+/// Autocomplete hook of an argument.
+#[derive(Clone)]
+pub struct Autocomplete(pub AutocompleteFunction);
+
+impl std::fmt::Debug for Autocomplete {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Autocomplete(_)")
+    }
+}
+
+/// Message component handler with the `custom_id` prefix that it responds to.
+#[derive(Clone)]
+pub struct ComponentHandler {
+    pub prefix: &'static str,
+    pub function: ComponentFunction,
+}
+
+impl std::fmt::Debug for ComponentHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComponentHandler")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FunctionKind {
     Classic,
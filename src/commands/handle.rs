@@ -1,29 +1,42 @@
 use std::sync::Arc;
 
 use tokio::task::JoinSet;
-use twilight_model::application::command::CommandType;
+use twilight_model::application::command::{
+    CommandOptionChoice, CommandOptionChoiceValue, CommandType,
+};
 use twilight_model::application::interaction::application_command::{
-    CommandData, CommandOptionValue,
+    CommandData, CommandDataOption, CommandOptionValue,
 };
+use twilight_model::application::interaction::message_component::MessageComponentInteractionData;
 use twilight_model::application::interaction::Interaction;
 use twilight_model::channel::message::MessageFlags;
 use twilight_model::channel::Message;
 use twilight_model::guild::Permissions;
-use twilight_model::http::interaction::{
-    InteractionResponse, InteractionResponseData, InteractionResponseType,
-};
+use twilight_model::http::interaction::{InteractionResponse, InteractionResponseType};
+use twilight_model::id::marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker};
+use twilight_model::id::Id;
+use twilight_util::builder::InteractionResponseDataBuilder;
 use twilight_util::permission_calculator::PermissionCalculator;
 
 use crate::commands::arg::{Arg, ArgValue, Ref};
 use crate::commands::builder::{
-    ArgDesc, ArgKind, BaseCommand, CommandFunction, CommandGroup, CommandOption,
+    channel_type_name, ArgDesc, ArgKind, BaseCommand, CommandFunction, CommandGroup, CommandOption,
+    Execution, FunctionKind, StringData,
 };
 use crate::commands::function::{Callable, ClassicFunction, SlashFunction};
+use crate::commands::incident::{Incident, IncidentId};
+use crate::commands::middleware::Invocation;
 use crate::commands::prelude::*;
+use crate::commands::request::reply_to_message;
+use crate::commands::ResponseCallFuture;
 use crate::utils::prelude::*;
 use crate::{parser, Context};
 
-const ERROR_MESSAGE: &str = "The bot has encountered an error executing the command! 😕";
+/// Maximum number of autocomplete choices that Discord accepts.
+const MAX_CHOICES: usize = 25;
+
+/// Maximum number of suggestions for an unknown command.
+const MAX_SUGGESTIONS: usize = 3;
 
 /// Handle interaction and execute command functions.
 pub async fn application_command(
@@ -39,6 +52,15 @@ pub async fn application_command(
         )));
     };
 
+    let channel_id = inter.channel.as_ref().map(|c| c.id);
+    let path = interaction_path(&data);
+
+    // Usage of the (sub)command for argument errors.
+    let usage = (data.kind == CommandType::ChatInput)
+        .then(|| Lookup::find(&base.command, &path[1..]))
+        .flatten()
+        .map(|lookup| lookup.synopsis("/", &path).join("\n"));
+
     let base = Arc::clone(base);
     let inter = Arc::new(inter);
     let data = Arc::new(data);
@@ -46,25 +68,33 @@ pub async fn application_command(
     // Process the command by kind.
     let result = {
         let inter = Arc::clone(&inter);
+        let data = Arc::clone(&data);
         match data.kind {
             CommandType::ChatInput => process_slash(ctx, base, inter, data).await,
             CommandType::Message => process_message(ctx, base, inter, data).await,
             CommandType::User => process_user(ctx, base, inter, data).await,
-            CommandType::Unknown(n) => panic!("Unknown command kind: {n}"),
-            other => panic!("Unhandled command kind: {other:?}"),
+            // Possibly from a stale command registration.
+            other => Err(anyhow::anyhow!("Unhandled command kind: {other:?}").into()),
         }
     };
 
     // Handle execution result.
     // Catch erroneous execution and clear dangling response.
     if let Err(e) = result {
-        ctx.interaction()
-            .create_followup(&inter.token)
-            .flags(MessageFlags::EPHEMERAL)
-            .content(ERROR_MESSAGE)?
-            .await
-            .context("Failed to send error message")?;
+        let e = report_incident(
+            ctx,
+            e,
+            format!("command '{}'", interaction_path(&data).join(" ")),
+            |incident| Incident {
+                guild_id: inter.guild_id,
+                channel_id,
+                user_id: inter.author_id(),
+                args: interaction_args(&data),
+                ..incident
+            },
+        );
 
+        error_reply(ctx, &inter, &e, usage.as_deref()).await?;
         return Err(e);
     }
 
@@ -78,9 +108,6 @@ async fn process_slash(
     inter: Arc<Interaction>,
     data: Arc<CommandData>,
 ) -> CommandResult<()> {
-    // Acknowledge the interaction.
-    normal_acknowledge(ctx, &inter).await?;
-
     let mut args = Vec::new();
     let mut last = Lookup::Command(&base.command);
     let mut data_opts = data.options.to_vec();
@@ -110,9 +137,10 @@ async fn process_slash(
                         last = sub; // Set last command or group found.
                     },
                     None => {
-                        // TODO: This should return error.
-                        error!("Subcommand or group not found: {}", opt.name);
-                        panic!("Subcommand or group not found: {}", opt.name);
+                        return Err(CommandError::NotFound(format!(
+                            "Subcommand or group '{}' does not exist",
+                            opt.name
+                        )));
                     },
                 }
             },
@@ -135,6 +163,11 @@ async fn process_slash(
                             }
                         }
 
+                        // Use objects that Discord resolved instead of ids.
+                        if let Some(resolved) = &data.resolved {
+                            arg = arg.resolved(resolved);
+                        }
+
                         // Args are still stored in reverse order.
                         args.push(Arg {
                             name: opt.name,
@@ -142,9 +175,11 @@ async fn process_slash(
                         });
                     },
                     Err(e) => {
-                        // TODO: This should return error.
-                        error!("Could not process argument: '{}': {e}", arg.kind().kind());
-                        panic!("Could not process argument: '{}': {e}", arg.kind().kind());
+                        return Err(CommandError::UnexpectedArgs(format!(
+                            "Could not process argument '{}' of type '{}': {e}",
+                            opt.name,
+                            arg.kind().kind()
+                        )));
                     },
                 }
             },
@@ -161,14 +196,21 @@ async fn process_slash(
     let req = SlashRequest::new(
         Arc::clone(&base),
         Arc::clone(&inter),
-        data,
+        Arc::clone(&data),
         Args::from(args),
     );
 
-    execute(ctx, funcs, req).await
+    let path = interaction_path(&data);
+    let deferral = req.clone();
+    let acknowledge = async {
+        if base.defers(FunctionKind::Slash) {
+            deferral.defer(ctx).await?;
+        }
+        Ok(())
+    };
+    execute(ctx, base.execution, funcs, req, &path, acknowledge).await
 }
 
-// TODO: See if any twilight resolved data can be used as objects instead of ids.
 /// Message GUI interaction commands.
 async fn process_message(
     ctx: &Context,
@@ -176,18 +218,26 @@ async fn process_message(
     inter: Arc<Interaction>,
     data: Arc<CommandData>,
 ) -> CommandResult<()> {
-    // Acknowledge the interaction.
-    ephemeral_acknowledge(ctx, &inter).await?;
-
-    // let data = data.resolved.as_ref().expect("Empty resolve error");
-    // for _message in &data.messages {} // Globally.
-
     let target = data.target_id.ok_or(CommandError::MissingArgs)?.cast();
-    let req = MessageRequest::new(Arc::clone(&base), inter, data, target);
-    execute(ctx, base.command.message(), req).await
+    let req = MessageRequest::new(
+        Arc::clone(&base),
+        Arc::clone(&inter),
+        Arc::clone(&data),
+        target,
+    );
+
+    let path = interaction_path(&data);
+    let deferral = req.clone();
+    let acknowledge = async {
+        if base.defers(FunctionKind::Message) {
+            deferral.defer(ctx).await?;
+        }
+        Ok(())
+    };
+    let funcs = base.command.message();
+    execute(ctx, base.execution, funcs, req, &path, acknowledge).await
 }
 
-// TODO: See if any twilight resolved data can be used as objects instead of ids.
 /// User GUI interaction commands.
 async fn process_user(
     ctx: &Context,
@@ -195,86 +245,373 @@ async fn process_user(
     inter: Arc<Interaction>,
     data: Arc<CommandData>,
 ) -> CommandResult<()> {
-    // Acknowledge the interaction.
-    ephemeral_acknowledge(ctx, &inter).await?;
+    let target = data.target_id.ok_or(CommandError::MissingArgs)?.cast();
+    let req = UserRequest::new(
+        Arc::clone(&base),
+        Arc::clone(&inter),
+        Arc::clone(&data),
+        target,
+    );
 
-    // let data = data.resolved.as_ref().expect("Empty resolve error");
-    // for _user in &data.users {} // Globally.
-    // for _member in &data.members {} // Guilds only.
+    let path = interaction_path(&data);
+    let deferral = req.clone();
+    let acknowledge = async {
+        if base.defers(FunctionKind::User) {
+            deferral.defer(ctx).await?;
+        }
+        Ok(())
+    };
+    let funcs = base.command.user();
+    execute(ctx, base.execution, funcs, req, &path, acknowledge).await
+}
 
-    let target = data.target_id.ok_or(CommandError::MissingArgs)?.cast();
-    let req = UserRequest::new(Arc::clone(&base), inter, data, target);
-    execute(ctx, base.command.user(), req).await
+/// The focused argument of an autocomplete interaction, along with the other filled arguments.
+struct Focused<'a> {
+    cmd: &'a CommandFunction,
+    name: String,
+    input: String,
+    args: Args,
+}
+
+/// Walk the interaction options through subcommands and groups to the focused argument.
+fn find_focused(
+    base: &BaseCommand,
+    mut options: Vec<CommandDataOption>,
+) -> CommandResult<Focused<'_>> {
+    let mut args = Vec::new();
+    let mut focused = None;
+    let mut lookup = Lookup::Command(&base.command);
+
+    // Process interaction until last (sub)command is found,
+    // while collecting the focused option and any other filled arguments.
+    while let Some(opt) = options.pop() {
+        match opt.value {
+            CommandOptionValue::SubCommand(next) | CommandOptionValue::SubCommandGroup(next) => {
+                let found = match lookup {
+                    Lookup::Command(c) => c
+                        .options
+                        .iter()
+                        .filter_map(Lookup::from_option)
+                        .find(|s| s.name() == opt.name),
+                    Lookup::Group(g) => g
+                        .subs
+                        .iter()
+                        .find(|s| s.name == opt.name)
+                        .map(Lookup::Command),
+                };
+
+                lookup = found.ok_or_else(|| {
+                    CommandError::NotFound(format!("Subcommand or group '{}'", opt.name))
+                })?;
+                options = next;
+            },
+            CommandOptionValue::Focused(input, _) => focused = Some((opt.name, input)),
+            value => {
+                // Arguments that cannot be converted are not useful for suggestions anyway.
+                if let Ok(value) = ArgValue::try_from(value) {
+                    args.push(Arg {
+                        name: opt.name,
+                        value,
+                    });
+                }
+            },
+        }
+    }
+
+    let Some((name, input)) = focused else {
+        return Err(CommandError::MissingArgs);
+    };
+
+    let Lookup::Command(cmd) = lookup else {
+        return Err(CommandError::UnexpectedArgs(format!(
+            "Expected command, found group '{}'",
+            lookup.name()
+        )));
+    };
+
+    args.reverse();
+
+    Ok(Focused {
+        cmd,
+        name,
+        input,
+        args: Args::from(args),
+    })
 }
 
-/// Creates a publicly visible loading state message.
-async fn normal_acknowledge(ctx: &Context, inter: &Interaction) -> AnyResult<()> {
-    let interaction = ctx.interaction();
+/// Handle autocomplete interaction and respond with suggested choices.
+pub async fn autocomplete(
+    ctx: &Context,
+    inter: Interaction,
+    data: CommandData,
+) -> CommandResult<()> {
+    // Lookup command from context.
+    let Some(base) = ctx.commands.get(data.name.as_str()) else {
+        return Err(CommandError::NotFound(format!(
+            "Command '{}' does not exist",
+            data.name
+        )));
+    };
+
+    // Owner commands are not suggested to others.
+    if check_owner(ctx, base, inter.author_id()).is_err() {
+        return Ok(());
+    }
+
+    let base = Arc::clone(base);
+    let Focused {
+        cmd,
+        name,
+        input,
+        args,
+    } = find_focused(&base, data.options.to_vec())?;
+
+    let Some(hook) = cmd
+        .args()
+        .find(|a| a.name == name)
+        .and_then(|a| a.kind.autocomplete())
+        .cloned()
+    else {
+        return Err(CommandError::UnexpectedArgs(format!(
+            "Argument '{name}' does not have autocomplete"
+        )));
+    };
+
+    let req = AutocompleteRequest::new(
+        Arc::clone(&base),
+        Arc::new(inter),
+        Arc::new(data),
+        args,
+        name,
+        input,
+    );
+
+    let choices = hook
+        .0
+        .call(ctx.to_owned(), req.clone())
+        .await?
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let value = match value {
+                ArgValue::String(s) => CommandOptionChoiceValue::String(s.into_string()),
+                ArgValue::Integer(i) => CommandOptionChoiceValue::Integer(i),
+                ArgValue::Number(n) => CommandOptionChoiceValue::Number(n),
+                _ => return None, // Other types cannot be choices.
+            };
+
+            Some(CommandOptionChoice {
+                name,
+                name_localizations: None,
+                value,
+            })
+        })
+        .take(MAX_CHOICES);
 
     let resp = InteractionResponse {
-        kind: InteractionResponseType::DeferredChannelMessageWithSource,
-        data: None,
+        kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+        data: Some(
+            InteractionResponseDataBuilder::new()
+                .choices(choices)
+                .build(),
+        ),
     };
 
-    interaction
-        .create_response(inter.id, &inter.token, &resp)
+    ctx.interaction()
+        .create_response(req.interaction.id, &req.interaction.token, &resp)
         .await?;
 
     Ok(())
 }
 
-/// Creates a personal loading state message.
-async fn ephemeral_acknowledge(ctx: &Context, inter: &Interaction) -> AnyResult<()> {
-    let interaction = ctx.interaction();
+/// Handle message component interaction and execute the registered component function.
+pub async fn message_component(
+    ctx: &Context,
+    inter: Interaction,
+    data: MessageComponentInteractionData,
+) -> CommandResult<()> {
+    // Lookup component handler from context.
+    let Some((base, handler)) = ctx.commands.component(&data.custom_id) else {
+        // Component is not registered, it could be awaited by standby instead.
+        trace!("No component handler for custom id '{}'", data.custom_id);
+        return Ok(());
+    };
+
+    let func = Arc::clone(&handler.function);
+    let origin = format!("component '{}'", data.custom_id);
+    let inter = Arc::new(inter);
+    let req = ComponentRequest::new(
+        Arc::clone(base),
+        Arc::clone(&inter),
+        Arc::new(data),
+        handler.prefix,
+    );
+
+    // Handlers are responsible for responding to the interaction themselves.
+    let path = [base.command.name];
+    let funcs = std::iter::once(func);
+    let result = execute(ctx, Execution::Concurrent, funcs, req, &path, async {
+        Ok(())
+    })
+    .await;
+
+    if let Err(e) = result {
+        let e = report_incident(ctx, e, origin, |incident| Incident {
+            guild_id: inter.guild_id,
+            channel_id: inter.channel.as_ref().map(|c| c.id),
+            user_id: inter.author_id(),
+            ..incident
+        });
+
+        error_reply(ctx, &inter, &e, None).await?;
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Reports an unexpected error as an incident, filling in the details with `details`.
+/// Returns the error with the incident id attached, so that the id can be shown to the user.
+fn report_incident(
+    ctx: &Context,
+    e: CommandError,
+    origin: String,
+    details: impl FnOnce(Incident) -> Incident,
+) -> CommandError {
+    // Errors caused by the user are not incidents.
+    if !matches!(
+        e.user_error(),
+        CommandError::Other(_) | CommandError::Multiple(_)
+    ) {
+        return e;
+    }
+
+    let e = match e {
+        CommandError::Other(e) => e,
+        e => anyhow::Error::new(e),
+    };
+
+    let id = IncidentId::new();
+    let incident = details(Incident::new(id, origin, &e));
 
+    tracing::Span::current().record("incident", tracing::field::display(id));
+    error!(incident = %id, "Failed {}: {}", incident.origin, incident.error);
+
+    // Report in the background, the user should not wait for it.
+    let ctx = ctx.clone();
+    tokio::spawn(async move {
+        if let Err(e) = ctx.incidents.report(&ctx, &incident).await {
+            warn!("{}", e.oneliner());
+        }
+    });
+
+    CommandError::Other(e.context(id))
+}
+
+/// Returns the message describing the error to the user, with the incident id if any.
+fn error_content(e: &CommandError, usage: Option<&str>) -> String {
+    let content = e.render(usage);
+    match e.incident() {
+        Some(id) => format!("{content}\nIncident ID: `{id}`"),
+        None => content,
+    }
+}
+
+/// Sends a personal message describing the error to the interaction,
+/// whether the interaction has been responded to or not.
+async fn error_reply(
+    ctx: &Context,
+    inter: &Interaction,
+    e: &CommandError,
+    usage: Option<&str>,
+) -> AnyResult<()> {
+    if e.is_shown() {
+        return Ok(());
+    }
+
+    let content = error_content(e, usage);
     let resp = InteractionResponse {
-        kind: InteractionResponseType::DeferredChannelMessageWithSource,
-        data: Some(InteractionResponseData {
-            flags: Some(MessageFlags::EPHEMERAL | MessageFlags::LOADING),
-            ..Default::default()
-        }),
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(
+            InteractionResponseDataBuilder::new()
+                .content(&content)
+                .flags(MessageFlags::EPHEMERAL)
+                .build(),
+        ),
     };
 
-    interaction
+    // Responding fails if the interaction has already been responded to.
+    if ctx
+        .interaction()
         .create_response(inter.id, &inter.token, &resp)
-        .await?;
+        .await
+        .is_ok()
+    {
+        return Ok(());
+    }
+
+    ctx.interaction()
+        .create_followup(&inter.token)
+        .flags(MessageFlags::EPHEMERAL)
+        .content(&content)?
+        .await
+        .context("Failed to send error message")?;
 
     Ok(())
 }
 
 /// Parse message and execute command functions.
 pub async fn classic_command(ctx: &Context, msg: Arc<Message>) -> CommandResult<()> {
-    // Unprefix the message contents.
-    let prefix = ctx.config.classic_prefix(msg.guild_id)?;
-    let Some((_, unprefixed)) = parser::unprefix_with([prefix], &msg.content) else {
-        return Err(CommandError::NotPrefixed);
+    // Unprefix the message contents, a mention of the bot also works as a prefix.
+    let mentions = [
+        format!("<@{}>", ctx.user.id),
+        format!("<@!{}>", ctx.user.id),
+    ];
+    let (unprefixed, mentioned) = match parser::unprefix_with(&mentions, &msg.content) {
+        Some((_, unprefixed)) => (unprefixed.trim_start(), true),
+        None => match ctx
+            .config
+            .classic_prefixes(msg.guild_id)?
+            .unprefix(&msg.content)
+        {
+            Some((_, unprefixed)) => (unprefixed, false),
+            None => return Err(CommandError::NotPrefixed),
+        },
     };
 
+    // Expand guild alias, if any. Commands take precedence over aliases.
+    let expanded = msg
+        .guild_id
+        .filter(|_| {
+            let (name, _) = parser::split_once_whitespace(unprefixed);
+            ctx.commands.get(name).is_none()
+        })
+        .and_then(|guild_id| {
+            let mut guild = ctx.config.guild(guild_id);
+            let aliases = guild.aliases().map_err(|e| debug!("{e}")).ok()?;
+            parser::expand_alias(aliases, unprefixed)
+        });
+    let unprefixed = expanded.as_deref().unwrap_or(unprefixed);
+
     // Get first possible command name.
     let (name, mut rest) = parser::split_once_whitespace(unprefixed);
 
     // Lookup command from context.
     let Some(base) = ctx.commands.get(name) else {
+        // Mentioning the bot does not have to be a command.
+        if mentioned {
+            return Err(CommandError::NotPrefixed);
+        }
+
+        suggest_commands(ctx, &msg, name).await?;
+
         return Err(CommandError::NotFound(format!(
             "Command '{name}' does not exist"
         )));
     };
 
-    // Check if command should run in DMs.
-    if !base.dm_enabled && msg.guild_id.is_none() {
-        return Err(CommandError::Disabled);
-    }
-
-    // Continue with access if there is no permission requirements.
-    if let Some(perms) = base.member_permissions {
-        // Return with error if the user does not have the permissions.
-        if !sender_has_permissions(ctx, &msg, perms).await? {
-            return Err(CommandError::AccessDenied);
-        }
-    }
-
     let base = Arc::new(base.to_owned());
     let mut lookup = Lookup::Command(&base.command);
+    let mut path = vec![base.command.name];
 
     // Parse contents until last (sub)command is found.
     loop {
@@ -290,6 +627,7 @@ pub async fn classic_command(ctx: &Context, msg: Arc<Message>) -> CommandResult<
         };
 
         if let Some(t) = found {
+            path.push(t.name());
             lookup = t;
             rest = next;
             continue;
@@ -298,59 +636,480 @@ pub async fn classic_command(ctx: &Context, msg: Arc<Message>) -> CommandResult<
         break;
     }
 
-    let args = match lookup {
-        Lookup::Command(c) => parse_classic_args(c, &msg, rest)?,
-        Lookup::Group(g) => {
-            return Err(CommandError::UnexpectedArgs(format!(
-                "Expected command, found group '{}'",
-                g.name
-            )));
-        },
-    };
+    // An unknown subcommand would otherwise be parsed as arguments.
+    let (next, _) = parser::split_once_whitespace(rest.unwrap_or("").trim_start());
+    if !next.is_empty() && lookup.expects_subcommand() {
+        // Subcommands are not suggested to those who cannot use the parent command.
+        if let Err(e) = check_classic_access(ctx, &msg, &base, &path).await {
+            classic_error_reply(ctx, &msg, &e, None).await?;
+            return Err(e);
+        }
 
-    let funcs = lookup
-        .classic_functions()
-        .context("Failed to get classic functions")?;
+        suggest_subcommands(ctx, &msg, &base, &lookup, &path, next).await?;
 
-    trace!(
-        "Creating classic request for '{name}' by user '{}'",
-        msg.author.id
-    );
+        return Err(CommandError::NotFound(format!(
+            "Subcommand '{} {next}' does not exist",
+            path.join(" ")
+        )));
+    }
 
-    let req = ClassicRequest::new(Arc::clone(&base), Arc::clone(&msg), args);
+    let prefix = ctx.config.classic_prefix(msg.guild_id).unwrap_or_default();
+    let usage = lookup.synopsis(&prefix, &path).join("\n");
 
-    debug!("Executing '{name}' by user '{}'", msg.author.id);
+    let result: CommandResult<()> = async {
+        let (cmd, args) = match lookup {
+            Lookup::Command(c) => (c, parse_classic_args(c, &msg, rest)),
+            Lookup::Group(g) => {
+                return Err(CommandError::UnexpectedArgs(format!(
+                    "Expected command, found group '{}'",
+                    g.name
+                )));
+            },
+        };
 
-    let response = execute(ctx, funcs, req).await;
+        let funcs = lookup
+            .classic_functions()
+            .context("Failed to get classic functions")?;
 
-    trace!("Completing '{name}' by user '{}'", msg.author.id);
+        trace!(
+            "Creating classic request for '{name}' by user '{}'",
+            msg.author.id
+        );
+
+        let req = ClassicRequest::new(
+            Arc::clone(&base),
+            Arc::clone(&msg),
+            args.as_ref().map_or_else(|_| Args::default(), Args::clone),
+        );
+
+        // Arguments are validated after the middleware has checked access and cooldown.
+        let validate = async { check_channel_types(ctx, cmd, &args?).await };
+        execute(ctx, base.execution, funcs, req, &path, validate).await
+    }
+    .await;
 
     // Handle execution result.
-    if let Err(e) = response {
-        ctx.http
-            .create_message(msg.channel_id)
-            .content(ERROR_MESSAGE)?
-            .await?;
+    if let Err(e) = result {
+        let e = report_incident(
+            ctx,
+            e,
+            format!("command '{}'", path.join(" ")),
+            |incident| Incident {
+                guild_id: msg.guild_id,
+                channel_id: Some(msg.channel_id),
+                user_id: Some(msg.author.id),
+                args: rest.unwrap_or_default().trim().to_string(),
+                ..incident
+            },
+        );
 
+        classic_error_reply(ctx, &msg, &e, Some(&usage)).await?;
         return Err(e);
     }
 
     Ok(())
 }
 
+/// Checks if the command can be used in DMs and if the sender is permitted to use the command path,
+/// and that it is not restricted in the guild.
+async fn check_classic_access(
+    ctx: &Context,
+    msg: &Message,
+    base: &BaseCommand,
+    path: &[&str],
+) -> CommandResult<()> {
+    check_owner(ctx, base, Some(msg.author.id))?;
+
+    // Check if command should run in DMs.
+    if !base.dm_enabled && msg.guild_id.is_none() {
+        return Err(CommandError::Disabled);
+    }
+
+    if !sender_permitted(ctx, msg, base, path).await? {
+        return Err(CommandError::AccessDenied);
+    }
+
+    let roles = msg.member.as_ref().map_or(&[][..], |m| &m.roles);
+    check_guild_settings(ctx, msg.guild_id, Some(msg.channel_id), roles, path)
+}
+
+/// Checks if the interaction user is permitted to use the command path, and that it is not restricted in the guild.
+fn check_interaction_access(
+    ctx: &Context,
+    inter: &Interaction,
+    base: &BaseCommand,
+    path: &[&str],
+) -> CommandResult<()> {
+    let channel_id = inter.channel.as_ref().map(|c| c.id);
+    let roles = inter.member.as_ref().map_or(&[][..], |m| &m.roles);
+
+    check_owner(ctx, base, inter.author_id())?;
+    check_denied(ctx, inter, roles, path)?;
+    check_guild_settings(ctx, inter.guild_id, channel_id, roles, path)
+}
+
+/// Calculate if the message sender is permitted to use the command path.
+/// Guild permission overrides take precedence over the required member permissions of the command.
+async fn sender_permitted(
+    ctx: &Context,
+    msg: &Message,
+    base: &BaseCommand,
+    path: &[&str],
+) -> CommandResult<bool> {
+    let roles = msg.member.as_ref().map_or(&[][..], |m| &m.roles);
+    if let Some(granted) = permission_override(ctx, msg.guild_id, msg.author.id, roles, path)? {
+        return Ok(granted);
+    }
+
+    // Continue with access if there is no permission requirements.
+    match base.member_permissions {
+        Some(perms) => sender_has_permissions(ctx, msg, perms).await,
+        None => Ok(true),
+    }
+}
+
+/// Returns true if the user is granted or false if denied the command path
+/// by the guild permission overrides, or `None` if not overridden.
+fn permission_override(
+    ctx: &Context,
+    guild_id: Option<Id<GuildMarker>>,
+    user_id: Id<UserMarker>,
+    roles: &[Id<RoleMarker>],
+    path: &[&str],
+) -> CommandResult<Option<bool>> {
+    let Some(guild_id) = guild_id else {
+        return Ok(None);
+    };
+
+    let mut guild = ctx.config.guild(guild_id);
+    Ok(guild
+        .settings()?
+        .permission_override(guild_id, user_id, roles, path))
+}
+
+/// Reply to the message with a message describing the error.
+async fn classic_error_reply(
+    ctx: &Context,
+    msg: &Message,
+    e: &CommandError,
+    usage: Option<&str>,
+) -> CommandResult<()> {
+    reply_to_message(ctx, msg, error_content(e, usage).into()).await
+}
+
+/// Reply with usage of the commands closest to an unknown command `name`, if any.
+/// Commands that the sender could not use are not suggested.
+async fn suggest_commands(ctx: &Context, msg: &Message, name: &str) -> CommandResult<()> {
+    let roles = msg.member.as_ref().map_or(&[][..], |m| &m.roles);
+    let mut usages = Vec::new();
+
+    for found in parser::closest_matches(name, ctx.commands.inner().keys().copied()) {
+        let Some(base) = ctx.commands.get(found) else {
+            continue;
+        };
+
+        if !base.command.has_classic() || (!base.dm_enabled && msg.guild_id.is_none()) {
+            continue;
+        }
+
+        if check_owner(ctx, base, Some(msg.author.id)).is_err() {
+            continue;
+        }
+
+        if !sender_permitted(ctx, msg, base, &[found]).await? {
+            continue;
+        }
+
+        if check_guild_settings(ctx, msg.guild_id, Some(msg.channel_id), roles, &[found]).is_err() {
+            continue;
+        }
+
+        usages.push(base.command.generate_help(0));
+        if usages.len() >= MAX_SUGGESTIONS {
+            break;
+        }
+    }
+
+    reply_suggestions(ctx, msg, name, usages).await
+}
+
+/// Reply with usage of the subcommands or groups of `lookup` closest to an unknown subcommand `name`, if any.
+/// The sender is already allowed to use the parent command at `path`,
+/// but subcommands that the sender could not use are not suggested.
+async fn suggest_subcommands(
+    ctx: &Context,
+    msg: &Message,
+    base: &BaseCommand,
+    lookup: &Lookup<'_>,
+    path: &[&'static str],
+    name: &str,
+) -> CommandResult<()> {
+    let subs = lookup.subcommands();
+    let parent = path.join(" ");
+    let mut usages = Vec::new();
+
+    for found in parser::closest_matches(name, subs.iter().map(|s| s.name())) {
+        let Some(sub) = subs.iter().find(|s| s.name() == found) else {
+            continue;
+        };
+
+        let mut path = path.to_vec();
+        path.push(sub.name());
+        if check_classic_access(ctx, msg, base, &path).await.is_err() {
+            continue;
+        }
+
+        usages.push(format!("{parent} {}", sub.usage()));
+        if usages.len() >= MAX_SUGGESTIONS {
+            break;
+        }
+    }
+
+    reply_suggestions(ctx, msg, &format!("{parent} {name}"), usages).await
+}
+
+/// Reply to an unknown command with the usage of suggested commands.
+/// Nothing is sent if there are no suggestions.
+async fn reply_suggestions(
+    ctx: &Context,
+    msg: &Message,
+    name: &str,
+    usages: Vec<String>,
+) -> CommandResult<()> {
+    if usages.is_empty() {
+        return Ok(());
+    }
+
+    let name = name.replace('`', "");
+    let content = format!(
+        "Unknown command `{name}`, did you mean:\n```yaml\n{}\n```",
+        usages.join("\n\n")
+    );
+
+    reply_to_message(ctx, msg, content.into()).await
+}
+
+/// Returns the arguments used in the interaction as text.
+fn interaction_args(data: &CommandData) -> String {
+    let mut args = Vec::new();
+    let mut options = &data.options;
+
+    while !options.is_empty() {
+        let mut next = None;
+
+        for opt in options.iter() {
+            match &opt.value {
+                CommandOptionValue::SubCommand(sub) | CommandOptionValue::SubCommandGroup(sub) => {
+                    next = Some(sub)
+                },
+                value => args.push(format!("{}: {value:?}", opt.name)),
+            }
+        }
+
+        let Some(next) = next else {
+            break;
+        };
+        options = next;
+    }
+
+    args.join(", ")
+}
+
+/// Returns the names of the command and any (sub)commands or groups used in the interaction.
+fn interaction_path(data: &CommandData) -> Vec<&str> {
+    let mut path = vec![data.name.as_str()];
+    let mut options = &data.options;
+
+    while let Some((name, next)) = options.iter().find_map(|o| match &o.value {
+        CommandOptionValue::SubCommand(next) | CommandOptionValue::SubCommandGroup(next) => {
+            Some((o.name.as_str(), next))
+        },
+        _ => None,
+    }) {
+        path.push(name);
+        options = next;
+    }
+
+    path
+}
+
+/// Check that the command path, or any of its parents, is not disabled or restricted in the guild.
+fn check_guild_settings(
+    ctx: &Context,
+    guild_id: Option<Id<GuildMarker>>,
+    channel_id: Option<Id<ChannelMarker>>,
+    roles: &[Id<RoleMarker>],
+    path: &[&str],
+) -> CommandResult<()> {
+    let Some(guild_id) = guild_id else {
+        return Ok(());
+    };
+
+    let mut guild = ctx.config.guild(guild_id);
+    let commands = &guild.settings()?.commands;
+
+    let allowed = (1..=path.len()).all(|n| {
+        commands
+            .get(&path[..n].join(" "))
+            .map_or(true, |c| c.allows(channel_id, roles))
+    });
+
+    if allowed {
+        Ok(())
+    } else {
+        Err(CommandError::Disabled)
+    }
+}
+
+/// Return an error if the interaction user is denied the command path by the guild permission overrides.
+/// Discord checks the member permissions and its own overrides before sending the interaction.
+fn check_denied(
+    ctx: &Context,
+    inter: &Interaction,
+    roles: &[Id<RoleMarker>],
+    path: &[&str],
+) -> CommandResult<()> {
+    let Some(user_id) = inter.author_id() else {
+        return Ok(());
+    };
+
+    match permission_override(ctx, inter.guild_id, user_id, roles, path)? {
+        Some(false) => Err(CommandError::AccessDenied),
+        _ => Ok(()),
+    }
+}
+
+/// A user invoking commands in a channel, used to find the commands that they can use.
+#[derive(Debug, Clone, Copy)]
+pub struct Invoker<'a> {
+    pub guild_id: Option<Id<GuildMarker>>,
+    pub channel_id: Option<Id<ChannelMarker>>,
+    pub user_id: Id<UserMarker>,
+    pub roles: &'a [Id<RoleMarker>],
+    /// Permissions of the member in the channel, `None` if not in a guild.
+    pub permissions: Option<Permissions>,
+}
+
+impl<'a> Invoker<'a> {
+    /// Create an invoker of the message sender.
+    pub async fn from_message(ctx: &Context, msg: &'a Message) -> CommandResult<Invoker<'a>> {
+        Ok(Self {
+            guild_id: msg.guild_id,
+            channel_id: Some(msg.channel_id),
+            user_id: msg.author.id,
+            roles: msg.member.as_ref().map_or(&[][..], |m| &m.roles),
+            permissions: sender_permissions(ctx, msg).await?,
+        })
+    }
+
+    /// Create an invoker of the interaction user, if any.
+    pub fn from_interaction(inter: &'a Interaction) -> Option<Self> {
+        Some(Self {
+            guild_id: inter.guild_id,
+            channel_id: inter.channel.as_ref().map(|c| c.id),
+            user_id: inter.author_id()?,
+            roles: inter.member.as_ref().map_or(&[][..], |m| &m.roles),
+            permissions: inter.member.as_ref().and_then(|m| m.permissions),
+        })
+    }
+
+    /// Returns true if the invoker can use the base command, or any of its subcommands, in the channel.
+    pub fn can_use(&self, ctx: &Context, base: &BaseCommand) -> CommandResult<bool> {
+        if check_owner(ctx, base, Some(self.user_id)).is_err()
+            || (!base.dm_enabled && self.guild_id.is_none())
+        {
+            return Ok(false);
+        }
+
+        for path in base.paths() {
+            if self.can_use_path(ctx, base, &path)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Returns true if the invoker is permitted to use the command path in the channel.
+    fn can_use_path(
+        &self,
+        ctx: &Context,
+        base: &BaseCommand,
+        path: &[&str],
+    ) -> CommandResult<bool> {
+        let permitted =
+            match permission_override(ctx, self.guild_id, self.user_id, self.roles, path)? {
+                Some(granted) => granted,
+                None => match (base.member_permissions, self.permissions) {
+                    (Some(required), Some(perms)) => {
+                        perms.contains(Permissions::ADMINISTRATOR) || perms.contains(required)
+                    },
+                    _ => true,
+                },
+            };
+
+        Ok(permitted
+            && check_guild_settings(ctx, self.guild_id, self.channel_id, self.roles, path).is_ok())
+    }
+}
+
+/// Return an error if the command is for the bot owner only and the user is someone else.
+pub fn check_owner(
+    ctx: &Context,
+    base: &BaseCommand,
+    user_id: Option<Id<UserMarker>>,
+) -> CommandResult<()> {
+    if base.owner_only && !user_id.map_or(false, |id| ctx.is_owner(id)) {
+        return Err(CommandError::AccessDenied);
+    }
+
+    Ok(())
+}
+
+/// Register a use of the command path, or return an error if it is on cooldown.
+fn check_cooldown(
+    ctx: &Context,
+    base: &BaseCommand,
+    path: &[&str],
+    user_id: Option<Id<UserMarker>>,
+    channel_id: Option<Id<ChannelMarker>>,
+    guild_id: Option<Id<GuildMarker>>,
+) -> CommandResult<()> {
+    let Some(cooldown) = base.cooldown else {
+        return Ok(());
+    };
+
+    let bucket = cooldown.scope.bucket(user_id, channel_id, guild_id);
+
+    ctx.cooldowns
+        .hit(&path.join(" "), &cooldown, bucket)
+        .map_err(|retry_after| CommandError::Cooldown { retry_after })
+}
+
 /// Calculate if the message sender has the `required` permissions.
 pub async fn sender_has_permissions(
     ctx: &Context,
     msg: &Message,
     required: Permissions,
 ) -> CommandResult<bool> {
+    // Return true if not in a guild.
+    Ok(sender_permissions(ctx, msg)
+        .await?
+        .map_or(true, |perms| perms.contains(required)))
+}
+
+/// Calculate the permissions of the message sender in the channel, or `None` if not in a guild.
+pub async fn sender_permissions(
+    ctx: &Context,
+    msg: &Message,
+) -> CommandResult<Option<Permissions>> {
     let Message {
         member: Some(member),
         guild_id: Some(guild_id),
         ..
     } = msg
     else {
-        return Ok(true); // Return true if not in a guild.
+        return Ok(None);
     };
 
     // `@everyone` role id is the same as the guild's id.
@@ -382,9 +1141,7 @@ pub async fn sender_has_permissions(
     // Get channel specific permission overwrites.
     let overwrites = channel.permission_overwrites.unwrap_or_default();
 
-    Ok(calc
-        .in_channel(channel.kind, &overwrites)
-        .contains(required))
+    Ok(Some(calc.in_channel(channel.kind, &overwrites)))
 }
 
 fn parse_classic_args(
@@ -394,32 +1151,134 @@ fn parse_classic_args(
 ) -> Result<Args, CommandError> {
     let mut parsed = Vec::new();
     let args: Vec<_> = cmd_fn.args().collect();
-    let mut split = args.iter().position(|a| !a.required).unwrap_or(args.len());
-    let mut parser = MessageParser::new(msg, rest);
+    let split = args.iter().position(|a| !a.required).unwrap_or(args.len());
 
-    // Process all the required args.
-    for arg in &args[..split] {
-        let arg = parser.parse_next(arg).context("Required argument error")?;
-        parsed.push(arg);
+    // Named flags can be given anywhere, the rest of the args are positional.
+    let (flags, positional) = match rest {
+        Some(rest) => parser::split_flags(
+            rest,
+            |name| {
+                args.iter()
+                    .any(|a| a.name == name && !matches!(a.kind, ArgKind::Bool))
+            },
+            |flags, idx| {
+                args.iter()
+                    .filter(|a| !flags.iter().any(|f| f.name == a.name))
+                    .nth(idx)
+                    .map_or(false, |a| a.is_rest())
+            },
+        )?,
+        None => (Vec::new(), String::new()),
+    };
+
+    if let Some(flag) = flags
+        .iter()
+        .find(|f| !args.iter().any(|a| a.name == f.name))
+    {
+        return Err(CommandError::UnexpectedArgs(format!(
+            "Unknown flag '--{}'",
+            flag.name
+        )));
     }
 
-    // Process rest of the args, if any.
-    for arg in &args[split..] {
-        let arg = match parser.parse_next(arg).context("Optional argument error") {
-            Ok(k) => k,
-            Err(e) => {
-                trace!("{e}");
-                continue;
-            },
+    let mut parser = MessageParser::new(msg, Some(positional.as_str()).filter(|p| !p.is_empty()));
+
+    for (idx, desc) in args.iter().enumerate() {
+        let arg = if let Some(flag) = flags.iter().find(|f| f.name == desc.name) {
+            // Process a named flag.
+            parse_flag(desc, flag).map_err(arg_error)?
+        } else if idx < split {
+            // Process a required arg.
+            parser.parse_next(desc).map_err(arg_error)?
+        } else {
+            // Process an optional arg, if any.
+            match parser.parse_next(desc).context("Optional argument error") {
+                Ok(k) => k,
+                Err(e) => {
+                    trace!("{e}");
+                    continue;
+                },
+            }
         };
 
+        check_arg(desc, &arg)?;
         parsed.push(arg);
-        split += 1;
     }
 
     Ok(Args::from(parsed))
 }
 
+/// Parse the value of a named flag. A bool flag without a value is true.
+fn parse_flag(desc: &ArgDesc, flag: &parser::Flag) -> AnyResult<Arg> {
+    let value = match (&desc.kind, &flag.value) {
+        (ArgKind::Bool, None) => ArgValue::Bool(true),
+        (kind, Some(value)) => ArgValue::from_kind(kind, value).with_context(|| {
+            format!("Expected a flag '--{}' of type '{}'", desc.name, desc.kind)
+        })?,
+        (_, None) => return Err(CommandError::MissingArgs.into()),
+    };
+
+    Ok(Arg {
+        name: desc.name.to_string(),
+        value,
+    })
+}
+
+/// Check a classic argument against the constraints of its description.
+fn check_arg(desc: &ArgDesc, arg: &Arg) -> CommandResult<()> {
+    arg.value
+        .check(&desc.kind)
+        .map_err(|e| CommandError::UnexpectedArgs(format!("Argument '{}' {e}", desc.name)))
+}
+
+/// Check that classic channel arguments are of the allowed channel types.
+async fn check_channel_types(
+    ctx: &Context,
+    cmd_fn: &CommandFunction,
+    args: &Args,
+) -> CommandResult<()> {
+    for desc in cmd_fn.args() {
+        let ArgKind::Channel(data) = &desc.kind else {
+            continue;
+        };
+        if data.channel_types.is_empty() || args.channel(desc.name).is_err() {
+            continue;
+        }
+
+        // The id may be of something else, or of a channel that the bot cannot access.
+        let channel = args.resolve_channel(ctx, desc.name).await.map_err(|e| {
+            debug!("Failed to resolve channel argument '{}': {e}", desc.name);
+            CommandError::UnexpectedArgs(format!(
+                "Argument '{}' is not a channel that the bot can access",
+                desc.name
+            ))
+        })?;
+        if !data.channel_types.contains(&channel.kind) {
+            let types: Vec<_> = data
+                .channel_types
+                .iter()
+                .map(|t| channel_type_name(*t))
+                .collect();
+            return Err(CommandError::UnexpectedArgs(format!(
+                "Argument '{}' must be a channel of type: {}",
+                desc.name,
+                types.join(", ")
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts an argument parsing error into a command error that can be shown to the user.
+fn arg_error(e: anyhow::Error) -> CommandError {
+    match e.chain().find_map(|e| e.downcast_ref::<CommandError>()) {
+        Some(CommandError::MissingArgs) => CommandError::MissingArgs,
+        Some(CommandError::ParseError(s)) => CommandError::ParseError(s.clone()),
+        _ => CommandError::ParseError(format!("{e}: {}", e.root_cause())),
+    }
+}
+
 /// Helper type for parsing args from a chat message.
 struct MessageParser<'a> {
     msg: &'a Message,
@@ -489,14 +1348,26 @@ impl<'a> MessageParser<'a> {
     // Parse text as a normal argument.
     fn parse_baseline(&mut self, kind: &ArgKind) -> AnyResult<ArgValue> {
         let unparsed = self.rest.ok_or(CommandError::MissingArgs)?;
+
+        // Consume everything left as is.
+        if let ArgKind::String(StringData { rest: true, .. }) = kind {
+            let value = unparsed.trim_start();
+            if value.is_empty() {
+                return Err(CommandError::MissingArgs.into());
+            }
+            self.rest = None;
+            return ArgValue::from_kind(kind, value);
+        }
+
         let (value, next) = parser::maybe_quoted_arg(unparsed)
             .with_context(|| format!("Failed to parse next argument from content '{unparsed}'"))?;
         self.rest = next;
-        ArgValue::from_kind(kind, value)
+        ArgValue::from_kind(kind, &value)
     }
 }
 
-enum Lookup<'a> {
+/// A (sub)command or a group found in a command tree.
+pub enum Lookup<'a> {
     Command(&'a CommandFunction),
     Group(&'a CommandGroup),
 }
@@ -510,13 +1381,82 @@ impl<'a> Lookup<'a> {
         }
     }
 
-    const fn name(&self) -> &str {
+    pub const fn name(&self) -> &'static str {
         match self {
             Lookup::Command(t) => t.name,
             Lookup::Group(t) => t.name,
         }
     }
 
+    /// Find the (sub)command or group at `path` under `command`.
+    pub fn find(command: &'a CommandFunction, path: &[&str]) -> Option<Self> {
+        path.iter()
+            .try_fold(Lookup::Command(command), |lookup, name| match lookup {
+                Lookup::Command(c) => c
+                    .options
+                    .iter()
+                    .filter_map(Lookup::from_option)
+                    .find(|t| t.name() == *name),
+                Lookup::Group(g) => g.subs.iter().find(|s| s.name == *name).map(Lookup::Command),
+            })
+    }
+
+    /// Returns the description.
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Lookup::Command(t) => t.description,
+            Lookup::Group(t) => t.description,
+        }
+    }
+
+    /// Returns usage help text.
+    pub fn usage(&self) -> String {
+        match self {
+            Lookup::Command(t) => t.generate_help(0),
+            Lookup::Group(t) => t.generate_help(0),
+        }
+    }
+
+    /// Returns usage syntax lines of this (sub)command or group at `path`,
+    /// each starting with `prefix` and the names of the parents in `path`.
+    /// Only classic subcommands are listed, unless the prefix is `/`.
+    pub fn synopsis(&self, prefix: &str, path: &[&str]) -> Vec<String> {
+        let classic = prefix != "/";
+        let parents = path.split_last().map_or(&[][..], |(_, parents)| parents);
+        let prefix = parents.iter().fold(prefix.to_string(), |prefix, name| {
+            format!("{prefix}{name} ")
+        });
+
+        match self {
+            Lookup::Command(t) => t.synopsis_with(&prefix, classic),
+            Lookup::Group(t) => t.synopsis_with(&prefix, classic),
+        }
+    }
+
+    /// Returns the subcommands and groups directly under this one.
+    pub fn children(&self) -> Vec<Lookup<'a>> {
+        match self {
+            Lookup::Command(c) => c.options.iter().filter_map(Lookup::from_option).collect(),
+            Lookup::Group(g) => g.subs.iter().map(Lookup::Command).collect(),
+        }
+    }
+
+    /// Returns the classic subcommands and groups directly under this one.
+    fn subcommands(&self) -> Vec<Lookup<'a>> {
+        self.children()
+            .into_iter()
+            .filter(|t| !matches!(t, Lookup::Command(s) if !s.has_classic()))
+            .collect()
+    }
+
+    /// Returns true if only a subcommand or a group can follow, instead of arguments.
+    fn expects_subcommand(&self) -> bool {
+        match self {
+            Lookup::Command(c) => !c.options.is_empty() && c.args().next().is_none(),
+            Lookup::Group(_) => true,
+        }
+    }
+
     fn classic_functions(&self) -> AnyResult<impl Iterator<Item = ClassicFunction> + '_> {
         match self {
             Lookup::Command(c) if c.has_classic() => Ok(c.classic()),
@@ -543,33 +1483,232 @@ impl<'a> Lookup<'a> {
     }
 }
 
-/// Execute tasks.
-async fn execute<I, F, R>(ctx: &Context, funcs: I, req: R) -> CommandResult<()>
+/// Converts an error of a command task, such as a panic, into a command error.
+fn join_error(e: tokio::task::JoinError) -> CommandError {
+    if !e.is_panic() {
+        return anyhow::Error::new(e)
+            .context("Execution task join error")
+            .into();
+    }
+
+    let panic = e.into_panic();
+    let message = panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown panic".to_string());
+
+    anyhow::anyhow!("Command function panicked: {message}").into()
+}
+
+/// Execute tasks within the middleware of the commands.
+/// `prepare` is run after the `before` hooks, such as to validate arguments or acknowledge the interaction.
+/// Every function is run along with its response, even if some of them fail.
+/// Errors from all of the functions are combined.
+async fn execute<I, F, R>(
+    ctx: &Context,
+    execution: Execution,
+    funcs: I,
+    req: R,
+    path: &[&str],
+    prepare: impl CommandFuture,
+) -> CommandResult<()>
 where
     I: Iterator<Item = F> + Send,
     F: Callable<R>,
-    R: Clone + Send,
+    R: Into<Request> + Clone + Send,
 {
-    let mut set = JoinSet::new();
-    let mut results = Vec::new();
+    let inv = Arc::new(Invocation::new(req.clone().into(), path));
+
+    let tasks = {
+        let inv = Arc::clone(&inv);
+        async move {
+            prepare.await?;
+
+            let mut errors = Vec::new();
+
+            match execution {
+                Execution::Concurrent => {
+                    let mut set = JoinSet::new();
+
+                    for func in funcs {
+                        let response = func.call(ctx.to_owned(), req.clone());
+                        set.spawn(respond(ctx.to_owned(), Arc::clone(&inv), response));
+                    }
+
+                    // Wait for completion.
+                    while let Some(task) = set.join_next().await {
+                        match task {
+                            Ok(Ok(())) => {},
+                            Ok(Err(e)) => errors.push(e),
+                            Err(e) => errors.push(join_error(e)),
+                        }
+                    }
+                },
+                Execution::Sequential => {
+                    for func in funcs {
+                        let response = func.call(ctx.to_owned(), req.clone());
+                        // Spawned to catch any panics.
+                        match tokio::spawn(respond(ctx.to_owned(), Arc::clone(&inv), response))
+                            .await
+                        {
+                            Ok(Ok(())) => {},
+                            Ok(Err(e)) => errors.push(e),
+                            Err(e) => errors.push(join_error(e)),
+                        }
+                    }
+                },
+            }
 
-    for func in funcs {
-        set.spawn(func.call(ctx.to_owned(), req.clone()));
-    }
+            CommandError::combine(errors)
+        }
+    };
+
+    run_middleware(ctx, &inv, tasks).await
+}
+
+/// Run the middleware of the commands around an execution.
+/// Access and cooldown of the invocation are checked after the `before` hooks,
+/// so that the middleware also sees the requests that are denied.
+pub async fn run_middleware(
+    ctx: &Context,
+    inv: &Invocation,
+    execution: impl CommandFuture,
+) -> CommandResult<()> {
+    let checked = async {
+        check_invocation(ctx, inv).await?;
+        execution.await
+    };
+
+    ctx.commands.middleware().run(ctx, inv, checked).await
+}
+
+/// Check that the user can use the invoked command path, then register the use for cooldown.
+/// Components and modal submits are checked against any path of the command, and do not count as uses.
+async fn check_invocation(ctx: &Context, inv: &Invocation) -> CommandResult<()> {
+    let base = inv.request.command();
+    let path = inv.path();
+
+    match &inv.request {
+        Request::Classic(req) => {
+            check_classic_access(ctx, &req.message, base, &path).await?;
 
-    // Wait for completion.
-    while let Some(task) = set.join_next().await {
-        results.push(task.context("Execution task join error"));
+            // The first edit of a command is executed again without counting as another use.
+            if ctx.replies.take_free_rerun(req.message.id) {
+                return Ok(());
+            }
+        },
+        Request::Slash(SlashRequest { interaction, .. })
+        | Request::Message(MessageRequest { interaction, .. })
+        | Request::User(UserRequest { interaction, .. }) => {
+            check_interaction_access(ctx, interaction, base, &path)?;
+        },
+        Request::Component(ComponentRequest { interaction, .. })
+        | Request::Modal(ModalSubmit { interaction, .. }) => {
+            let permitted = match Invoker::from_interaction(interaction) {
+                Some(invoker) => invoker.can_use(ctx, base)?,
+                None => false,
+            };
+
+            return if permitted {
+                Ok(())
+            } else {
+                Err(CommandError::AccessDenied)
+            };
+        },
     }
 
-    // This should not fail.
-    let last = results.pop().expect("No results from command handlers");
+    check_cooldown(
+        ctx,
+        base,
+        &path,
+        inv.request.user_id(),
+        inv.request.channel_id(),
+        inv.request.guild_id(),
+    )
+}
+
+/// Complete the response of a command function, after passing it through the middleware.
+async fn respond(
+    ctx: Context,
+    inv: Arc<Invocation>,
+    response: ResponseCallFuture,
+) -> CommandResult<()> {
+    let response = response.await?;
+    ctx.commands
+        .middleware()
+        .respond(&ctx, &inv, response)
+        .await?
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::application::command::CommandOptionType;
 
-    for r in results {
-        // TODO: Collect all errors and responses.
-        // Prioritize returning errors immediately, for now.
-        r?.ok();
+    use super::*;
+    use crate::commands::function::mock;
+
+    async fn suggest(_ctx: Context, _req: AutocompleteRequest) -> AutocompleteResponse {
+        Ok(Vec::new())
     }
 
-    last?.context("Execution result").map_err(Into::into)
+    #[test]
+    fn focused_in_group() {
+        use crate::commands::builder::*;
+
+        let base: BaseCommand = command("a", "description")
+            .attach(mock::slash)
+            .option(
+                group("b", "description").option(
+                    sub("c", "description")
+                        .attach(mock::slash)
+                        .option(integer("count", "description"))
+                        .option(string("name", "description").autocomplete(suggest)),
+                ),
+            )
+            .into();
+
+        let option = |name: &str, value| CommandDataOption {
+            name: name.to_string(),
+            value,
+        };
+        let options = vec![option(
+            "b",
+            CommandOptionValue::SubCommandGroup(vec![option(
+                "c",
+                CommandOptionValue::SubCommand(vec![
+                    option("count", CommandOptionValue::Integer(3)),
+                    option(
+                        "name",
+                        CommandOptionValue::Focused("ab".to_string(), CommandOptionType::String),
+                    ),
+                ]),
+            )]),
+        )];
+
+        let focused = find_focused(&base, options).unwrap();
+        assert_eq!("c", focused.cmd.name);
+        assert_eq!(
+            ("name", "ab"),
+            (focused.name.as_str(), focused.input.as_str())
+        );
+        assert_eq!(3, focused.args.integer("count").unwrap());
+        assert!(focused
+            .cmd
+            .args()
+            .find(|a| a.name == focused.name)
+            .and_then(|a| a.kind.autocomplete())
+            .is_some());
+
+        // The focused argument must be in a subcommand.
+        let options = vec![option(
+            "b",
+            CommandOptionValue::SubCommandGroup(vec![option(
+                "name",
+                CommandOptionValue::Focused("ab".to_string(), CommandOptionType::String),
+            )]),
+        )];
+        assert!(find_focused(&base, options).is_err());
+    }
 }
@@ -0,0 +1,297 @@
+//! Incident reports of failed command executions and events.
+//!
+//! Each incident gets a short id, which is shown to the user, logged and sent to the bot-dev channel,
+//! so that a user's report can be linked to the error.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use twilight_model::channel::message::Embed;
+use twilight_model::http::attachment::Attachment;
+use twilight_model::id::marker::{ChannelMarker, GuildMarker, UserMarker};
+use twilight_model::id::Id;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder};
+
+use crate::utils::prelude::*;
+use crate::Context;
+
+/// Environment variable for a channel where incidents are reported.
+pub const BOTDEV_CHANNEL_VAR: &str = "DISCORD_BOTDEV_CHANNEL";
+
+/// Short random id of an incident.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IncidentId(u32);
+
+impl IncidentId {
+    /// Create a new random id.
+    pub fn new() -> Self {
+        Self(rand::random::<u32>() & 0xFF_FFFF)
+    }
+}
+
+impl Default for IncidentId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for IncidentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:06X}", self.0)
+    }
+}
+
+/// Information about an error that should be looked into.
+#[derive(Debug, Clone)]
+pub struct Incident {
+    pub id: IncidentId,
+    /// What failed, such as a command path or an event name.
+    pub origin: String,
+    pub guild_id: Option<Id<GuildMarker>>,
+    pub channel_id: Option<Id<ChannelMarker>>,
+    pub user_id: Option<Id<UserMarker>>,
+    /// Arguments of the command, if any.
+    pub args: String,
+    /// Error chain as one line.
+    pub error: String,
+    /// Full error with any backtrace.
+    pub details: String,
+}
+
+impl Incident {
+    /// Create a new incident from an error.
+    pub fn new(id: IncidentId, origin: impl Into<String>, error: &anyhow::Error) -> Self {
+        Self {
+            id,
+            origin: origin.into(),
+            guild_id: None,
+            channel_id: None,
+            user_id: None,
+            args: String::new(),
+            error: error.oneliner(),
+            details: format!("{error:?}"),
+        }
+    }
+
+    /// Returns a hash of the origin and the error, used to find repeated incidents.
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.origin.hash(&mut hasher);
+        self.error.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Create the report embed, mentioning suppressed `repeats` of the same incident.
+    fn embed(&self, repeats: u32) -> AnyResult<Embed> {
+        let id_or_none =
+            |id: Option<u64>| id.map_or_else(|| "None".to_string(), |id| id.to_string());
+
+        let mut embed = EmbedBuilder::new()
+            .title(format!("Incident `{}`", self.id))
+            .field(EmbedFieldBuilder::new("Origin", truncate(&self.origin, 256)).inline())
+            .field(EmbedFieldBuilder::new("Guild", id_or_none(self.guild_id.map(Id::get))).inline())
+            .field(
+                EmbedFieldBuilder::new("Channel", id_or_none(self.channel_id.map(Id::get)))
+                    .inline(),
+            )
+            .field(EmbedFieldBuilder::new("User", id_or_none(self.user_id.map(Id::get))).inline());
+
+        if !self.args.is_empty() {
+            embed = embed.field(EmbedFieldBuilder::new(
+                "Arguments",
+                format!("```{}```", truncate(&self.args, Incidents::FIELD_LIMIT)),
+            ));
+        }
+
+        embed = embed.field(EmbedFieldBuilder::new(
+            "Error",
+            format!("```{}```", truncate(&self.error, Incidents::FIELD_LIMIT)),
+        ));
+
+        if repeats > 0 {
+            embed = embed.footer(EmbedFooterBuilder::new(format!(
+                "Repeated {repeats} times since the last report"
+            )));
+        }
+
+        Ok(embed.validate()?.build())
+    }
+}
+
+/// Returns `text` truncated to at most `max` characters, marking if it was truncated.
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(max.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Returns the bot-dev channel id, if set.
+pub fn botdev_channel() -> AnyResult<Option<Id<ChannelMarker>>> {
+    env::var(BOTDEV_CHANNEL_VAR)
+        .ok()
+        .map(|id| id.parse())
+        .transpose()
+        .with_context(|| format!("Invalid channel id in '{BOTDEV_CHANNEL_VAR}'"))
+}
+
+/// Recent reports of the same incident.
+#[derive(Debug)]
+struct Recent {
+    last: Instant,
+    repeats: u32,
+}
+
+/// Tracker for deduplicating and rate limiting incident reports.
+#[derive(Debug, Default)]
+pub struct Incidents {
+    recent: Mutex<HashMap<u64, Recent>>,
+    sent: Mutex<VecDeque<Instant>>,
+}
+
+impl Incidents {
+    /// Period in which identical incidents are reported only once.
+    const DEDUP_PERIOD: Duration = Duration::from_secs(10 * 60);
+    /// Maximum length of an embed field value, with room for formatting.
+    const FIELD_LIMIT: usize = 1000;
+    /// Maximum number of reports sent within `RATE_PERIOD`.
+    const RATE_LIMIT: usize = 5;
+    /// Period in which at most `RATE_LIMIT` reports are sent.
+    const RATE_PERIOD: Duration = Duration::from_secs(60);
+
+    /// Send an incident report to the bot-dev channel, if set.
+    /// Repeated and excessive reports are suppressed.
+    pub async fn report(&self, ctx: &Context, incident: &Incident) -> AnyResult<()> {
+        let Some(channel_id) = botdev_channel()? else {
+            return Ok(());
+        };
+
+        let Some(repeats) = self.check(incident.fingerprint(), Instant::now()) else {
+            debug!("Incident '{}' report suppressed", incident.id);
+            return Ok(());
+        };
+
+        let embeds = [incident.embed(repeats)?];
+
+        // Attach the full error if it does not fit in the embed.
+        let attachments = if incident.details.chars().count() > Self::FIELD_LIMIT {
+            vec![Attachment::from_bytes(
+                format!("incident-{}.txt", incident.id),
+                incident.details.clone().into_bytes(),
+                0,
+            )]
+        } else {
+            Vec::new()
+        };
+
+        let mut req = ctx.http.create_message(channel_id).embeds(&embeds)?;
+        if !attachments.is_empty() {
+            req = req.attachments(&attachments)?;
+        }
+
+        req.await
+            .with_context(|| format!("Failed to send incident '{}' report", incident.id))?;
+
+        Ok(())
+    }
+
+    /// Register an incident with `fingerprint`.
+    /// Returns the number of suppressed repeats since the last report, if the incident should be reported.
+    fn check(&self, fingerprint: u64, now: Instant) -> Option<u32> {
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Forget incidents that have not been repeated in a while.
+        recent.retain(|_, r| now.duration_since(r.last) < Self::DEDUP_PERIOD * 2);
+
+        let repeats = match recent.get_mut(&fingerprint) {
+            Some(r) if now.duration_since(r.last) < Self::DEDUP_PERIOD => {
+                r.repeats += 1;
+                return None;
+            },
+            Some(r) => r.repeats,
+            None => 0,
+        };
+
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Drop reports that are outside of the sliding window.
+        while sent
+            .front()
+            .map_or(false, |t| now.duration_since(*t) >= Self::RATE_PERIOD)
+        {
+            sent.pop_front();
+        }
+
+        if sent.len() >= Self::RATE_LIMIT {
+            if let Some(r) = recent.get_mut(&fingerprint) {
+                r.repeats += 1;
+            }
+            return None;
+        }
+
+        sent.push_back(now);
+        recent.insert(fingerprint, Recent {
+            last: now,
+            repeats: 0,
+        });
+
+        Some(repeats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandError;
+
+    #[test]
+    fn find_reported_command() {
+        let id = IncidentId::new();
+
+        // Same shape as a failed classic command passed to the event handler.
+        let result: Result<(), CommandError> =
+            Err(CommandError::Other(anyhow::anyhow!("Failed").context(id)));
+        let e = result
+            .context("Failed to handle classic command")
+            .unwrap_err();
+
+        assert_eq!(
+            Some(id),
+            CommandError::find(&e).and_then(CommandError::incident)
+        );
+
+        let e = anyhow::anyhow!("Failed").context("Failed to handle event");
+        assert!(CommandError::find(&e).is_none());
+    }
+
+    #[test]
+    fn deduplicate_and_rate_limit() {
+        let incidents = Incidents::default();
+        let now = Instant::now();
+
+        assert_eq!(Some(0), incidents.check(1, now));
+        assert_eq!(None, incidents.check(1, now + Duration::from_secs(1)));
+        assert_eq!(None, incidents.check(1, now + Duration::from_secs(2)));
+
+        // Reported again after the period, with the suppressed repeats.
+        assert_eq!(Some(2), incidents.check(1, now + Incidents::DEDUP_PERIOD));
+
+        // Different incidents are limited by rate.
+        let later = now + Incidents::DEDUP_PERIOD * 2;
+        for fingerprint in 2..2 + Incidents::RATE_LIMIT as u64 {
+            assert_eq!(Some(0), incidents.check(fingerprint, later));
+        }
+        assert_eq!(None, incidents.check(100, later));
+        assert_eq!(
+            Some(0),
+            incidents.check(100, later + Incidents::RATE_PERIOD)
+        );
+    }
+}
@@ -0,0 +1,411 @@
+//! Middleware around command execution.
+//!
+//! Middleware registered on the commands is run around the execution of every command, component handler and modal submit,
+//! in the order they were registered for `before`, and in reverse order for `after` and `on_error`.
+//! It can deny a request by returning an error, and replace or wrap the responses of the functions.
+//! The access and cooldown of a request are always checked after the `before` hooks,
+//! so that the middleware also sees the requests that are denied.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use twilight_model::id::Id;
+
+use crate::commands::prelude::*;
+use crate::utils::prelude::*;
+
+/// A request to execute a command, passed to the middleware.
+#[derive(Debug, Clone)]
+pub struct Invocation {
+    pub request: Request,
+    /// Path of the invoked (sub)command.
+    path: Vec<String>,
+    /// When the middleware started running for the request.
+    pub started: Instant,
+}
+
+impl Invocation {
+    /// Create an invocation of the command `path`, starting now.
+    pub fn new(request: Request, path: &[&str]) -> Self {
+        Self {
+            request,
+            path: path.iter().map(|s| s.to_string()).collect(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Returns the name of the base command.
+    pub fn name(&self) -> &'static str {
+        self.request.command().command.name
+    }
+
+    /// Returns the path of the invoked (sub)command, starting with the base command.
+    pub fn path(&self) -> Vec<&str> {
+        self.path.iter().map(String::as_str).collect()
+    }
+
+    /// Returns the kind of the request, such as `slash`.
+    pub fn kind(&self) -> &'static str {
+        match self.request {
+            Request::Classic(_) => "classic",
+            Request::Slash(_) => "slash",
+            Request::Message(_) => "message",
+            Request::User(_) => "user",
+            Request::Component(_) => "component",
+            Request::Modal(_) => "modal",
+        }
+    }
+}
+
+/// Hooks that are run around command execution.
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Called before the command is executed.
+    /// Returning an error skips the execution, and the error is handled like one from the command.
+    async fn before(&self, _ctx: &Context, _inv: &Invocation) -> CommandResult<()> {
+        Ok(())
+    }
+
+    /// Called with the response of each command function before it is completed.
+    /// The response can be replaced, or wrapped with `Response::new`.
+    async fn after(
+        &self,
+        _ctx: &Context,
+        _inv: &Invocation,
+        response: Response,
+    ) -> CommandResponse {
+        Ok(response)
+    }
+
+    /// Called with an error from the execution, the command functions, their responses or the middleware.
+    /// The error can be replaced, or cleared by returning `Ok`, which skips the remaining middleware.
+    async fn on_error(
+        &self,
+        _ctx: &Context,
+        _inv: &Invocation,
+        error: CommandError,
+    ) -> CommandResult<()> {
+        Err(error)
+    }
+}
+
+/// Middleware registered on the commands.
+#[derive(Clone, Default)]
+pub struct Middlewares(Vec<Arc<dyn Middleware>>);
+
+impl Middlewares {
+    /// Add a middleware to the end of the list.
+    pub fn push(&mut self, middleware: impl Middleware + 'static) {
+        self.0.push(Arc::new(middleware));
+    }
+
+    /// Run the middleware around the execution of a command.
+    /// The command is not executed if any of the `before` hooks fail.
+    pub async fn run(
+        &self,
+        ctx: &Context,
+        inv: &Invocation,
+        execution: impl CommandFuture,
+    ) -> CommandResult<()> {
+        run_hooks(
+            &self.0,
+            |m| m.before(ctx, inv),
+            execution,
+            |m, e| m.on_error(ctx, inv, e),
+        )
+        .await
+    }
+
+    /// Pass the response of a command function through the `after` hooks.
+    pub async fn respond(
+        &self,
+        ctx: &Context,
+        inv: &Invocation,
+        response: Response,
+    ) -> CommandResponse {
+        after_hooks(&self.0, response, |m, r| m.after(ctx, inv, r)).await
+    }
+}
+
+impl std::fmt::Debug for Middlewares {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Middlewares({})", self.0.len())
+    }
+}
+
+/// Run the `before` hooks in order and then `execution`,
+/// or the `on_error` hooks in reverse order if any of them fail.
+async fn run_hooks<'a, T, B, E>(
+    list: &'a [T],
+    before: impl Fn(&'a T) -> B,
+    execution: impl CommandFuture,
+    on_error: impl Fn(&'a T, CommandError) -> E,
+) -> CommandResult<()>
+where
+    B: CommandFuture,
+    E: CommandFuture,
+{
+    let result = async {
+        for item in list {
+            before(item).await?;
+        }
+
+        execution.await
+    }
+    .await;
+
+    let Err(mut error) = result else {
+        return Ok(());
+    };
+
+    for item in list.iter().rev() {
+        match on_error(item, error).await {
+            Ok(()) => return Ok(()),
+            Err(e) => error = e,
+        }
+    }
+
+    Err(error)
+}
+
+/// Pass the response through the `after` hooks in reverse order.
+async fn after_hooks<'a, T, A>(
+    list: &'a [T],
+    mut response: Response,
+    after: impl Fn(&'a T, Response) -> A,
+) -> CommandResponse
+where
+    A: ResponseFuture,
+{
+    for item in list.iter().rev() {
+        response = after(item, response).await?;
+    }
+
+    Ok(response)
+}
+
+/// Logs how long command functions take to complete, warning about slow ones.
+#[derive(Debug, Clone, Copy)]
+pub struct Timing {
+    /// Duration after which a command is considered slow.
+    pub slow: Duration,
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self {
+            slow: Duration::from_secs(5),
+        }
+    }
+}
+
+impl Timing {
+    /// Log the time since the invocation started.
+    fn log(&self, inv: &Invocation, outcome: &str) {
+        let elapsed = inv.started.elapsed();
+        if elapsed >= self.slow {
+            warn!("Slow '{}' {outcome} in {elapsed:?}", inv.name());
+        } else {
+            trace!("'{}' {outcome} in {elapsed:?}", inv.name());
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for Timing {
+    async fn after(&self, _ctx: &Context, inv: &Invocation, response: Response) -> CommandResponse {
+        let (timing, inv) = (*self, inv.clone());
+
+        Ok(Response::new(move || async move {
+            let result = response.await;
+            if result.is_ok() {
+                timing.log(&inv, "completed");
+            }
+            result
+        }))
+    }
+
+    async fn on_error(
+        &self,
+        _ctx: &Context,
+        inv: &Invocation,
+        error: CommandError,
+    ) -> CommandResult<()> {
+        self.log(inv, "failed");
+        Err(error)
+    }
+}
+
+/// Logs who used which command and where, along with any failure, including denied requests.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuditLog;
+
+#[async_trait]
+impl Middleware for AuditLog {
+    async fn before(&self, _ctx: &Context, inv: &Invocation) -> CommandResult<()> {
+        let id_or_none =
+            |id: Option<u64>| id.map_or_else(|| "None".to_string(), |id| id.to_string());
+
+        debug!(
+            "Executing {} '{}' by user '{}' in guild '{}', channel '{}'",
+            inv.kind(),
+            inv.path().join(" "),
+            id_or_none(inv.request.user_id().map(Id::get)),
+            id_or_none(inv.request.guild_id().map(Id::get)),
+            id_or_none(inv.request.channel_id().map(Id::get)),
+        );
+
+        Ok(())
+    }
+
+    async fn on_error(
+        &self,
+        _ctx: &Context,
+        inv: &Invocation,
+        error: CommandError,
+    ) -> CommandResult<()> {
+        debug!("{} '{}' failed: {error}", inv.kind(), inv.path().join(" "));
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::ready;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Hook that records its calls, failing or recovering as configured.
+    struct Hook {
+        name: &'static str,
+        deny: bool,
+        recover: bool,
+    }
+
+    const fn hook(name: &'static str) -> Hook {
+        Hook {
+            name,
+            deny: false,
+            recover: false,
+        }
+    }
+
+    /// Run an execution within the hooks, returning the result and the recorded calls.
+    async fn run(hooks: &[Hook], fail: bool) -> (CommandResult<()>, Vec<String>) {
+        let calls = Mutex::new(Vec::new());
+        let record = |call: String| calls.lock().unwrap().push(call);
+
+        let result = run_hooks(
+            hooks,
+            |h| {
+                record(format!("before {}", h.name));
+                ready(if h.deny {
+                    Err(CommandError::AccessDenied)
+                } else {
+                    Ok(())
+                })
+            },
+            async {
+                record("execute".to_string());
+                if fail {
+                    Err(CommandError::Disabled)
+                } else {
+                    Ok(())
+                }
+            },
+            |h, e| {
+                record(format!("on_error {}", h.name));
+                ready(if h.recover { Ok(()) } else { Err(e) })
+            },
+        )
+        .await;
+
+        (result, calls.into_inner().unwrap())
+    }
+
+    #[tokio::test]
+    async fn hook_order() {
+        let (result, calls) = run(&[hook("a"), hook("b")], false).await;
+        assert!(result.is_ok());
+        assert_eq!(calls, ["before a", "before b", "execute"]);
+
+        let (result, calls) = run(&[hook("a"), hook("b")], true).await;
+        assert!(matches!(result, Err(CommandError::Disabled)));
+        assert_eq!(calls, [
+            "before a",
+            "before b",
+            "execute",
+            "on_error b",
+            "on_error a",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn short_circuit() {
+        // A failing `before` hook skips the execution and the rest of the `before` hooks.
+        let deny = Hook {
+            deny: true,
+            ..hook("b")
+        };
+        let (result, calls) = run(&[hook("a"), deny, hook("c")], false).await;
+        assert!(matches!(result, Err(CommandError::AccessDenied)));
+        assert_eq!(calls, [
+            "before a",
+            "before b",
+            "on_error c",
+            "on_error b",
+            "on_error a",
+        ]);
+
+        // A recovering `on_error` hook skips the rest of the `on_error` hooks.
+        let recover = Hook {
+            recover: true,
+            ..hook("b")
+        };
+        let (result, calls) = run(&[hook("a"), recover, hook("c")], true).await;
+        assert!(result.is_ok());
+        assert_eq!(calls, [
+            "before a",
+            "before b",
+            "before c",
+            "execute",
+            "on_error c",
+            "on_error b",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn after_order() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let hooks = [hook("a"), hook("b")];
+
+        let response = {
+            let calls = Arc::clone(&calls);
+            Response::new(move || async move {
+                calls.lock().unwrap().push("respond".to_string());
+                Ok(())
+            })
+        };
+
+        // Hooks are called in reverse, so the first hook wraps the response last.
+        let response = after_hooks(&hooks, response, |h, response| {
+            let (calls, name) = (Arc::clone(&calls), h.name);
+            calls.lock().unwrap().push(format!("after {name}"));
+            ready(Ok(Response::new(move || async move {
+                calls.lock().unwrap().push(format!("complete {name}"));
+                response.await
+            })))
+        })
+        .await;
+
+        assert!(response.unwrap().await.is_ok());
+        assert_eq!(*calls.lock().unwrap(), [
+            "after b",
+            "after a",
+            "complete a",
+            "complete b",
+            "respond",
+        ]);
+    }
+}
@@ -35,34 +35,45 @@ use std::collections::{BTreeMap, HashSet};
 use std::mem;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
 use derive_more::{Deref, DerefMut, Index, IntoIterator};
 use futures::Future;
 use thiserror::Error;
 
+use crate::commands::arg::ArgValue;
 use crate::commands::builder::twilight::{CommandValidationError, TwilightCommand};
-use crate::commands::builder::BaseCommand;
-use crate::commands::request::Request;
+use crate::commands::builder::{BaseCommand, Category};
+use crate::commands::function::ComponentHandler;
+use crate::commands::incident::IncidentId;
+use crate::commands::middleware::{Middleware, Middlewares};
+use crate::commands::request::{Reply, Request};
 use crate::utils::prelude::*;
 use crate::{BotEvent, Context};
 
 pub mod arg;
 pub mod bot;
 pub mod builder;
+pub mod cooldown;
 pub mod function;
 pub mod handle;
+pub mod incident;
+pub mod middleware;
+pub mod replies;
 pub mod request;
+pub mod sync;
 
 /// Prelude module for command things.
 pub mod prelude {
-    pub use crate::commands::arg::{ArgValueExt, Args};
+    pub use crate::commands::arg::{from_args, ArgValue, ArgValueExt, Args, FromArgs};
     pub use crate::commands::builder::BaseCommand;
     pub use crate::commands::request::{
-        ClassicRequest, MessageRequest, Request, SlashRequest, UserRequest,
+        AutocompleteRequest, ClassicRequest, ComponentRequest, MessageRequest, ModalSubmit, Reply,
+        Request, Responder, SlashRequest, UserRequest,
     };
     pub use crate::commands::{
-        CallFuture, CommandError, CommandFuture, CommandResponse, CommandResult, Response,
-        ResponseFuture,
+        AutocompleteResponse, CallFuture, CommandError, CommandFuture, CommandResponse,
+        CommandResult, Response, ResponseFuture,
     };
     pub use crate::Context;
 }
@@ -109,15 +120,121 @@ pub enum CommandError {
     #[error("Command or action disabled")]
     Disabled,
 
+    /// The command is on cooldown.
+    #[error("Slow down! Try again in {}s.", .retry_after.as_secs_f64().ceil())]
+    Cooldown { retry_after: Duration },
+
+    /// The user did not respond in time.
+    #[error("Timed out waiting for user input")]
+    Timeout,
+
     /// The sender does not have permissions needed.
     #[error("Permission requirements not met")]
     AccessDenied,
 
+    /// Errors from multiple attached functions.
+    #[error("Multiple errors: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    Multiple(Vec<CommandError>),
+
     /// Other errors that are or can be converted to `anyhow::Error`.
     #[error(transparent)]
     Other(#[from] anyhow::Error), // Source and Display delegate to `anyhow::Error`
 }
 
+impl CommandError {
+    /// Generic message for errors that the user cannot do anything about.
+    pub const GENERIC_MESSAGE: &'static str =
+        "The bot has encountered an error executing the command! 😕";
+
+    /// Returns the error that the user should be told about.
+    /// Command errors wrapped in `Other`, such as with added context, are found from the error chain.
+    /// Of multiple errors, the first one caused by the user is returned.
+    pub fn user_error(&self) -> &Self {
+        match self {
+            Self::Other(e) => e
+                .chain()
+                .find_map(|e| e.downcast_ref::<Self>())
+                .filter(|e| !matches!(e, Self::Other(_)))
+                .unwrap_or(self),
+            Self::Multiple(errors) => errors
+                .iter()
+                .map(Self::user_error)
+                .find(|e| !matches!(e, Self::Other(_) | Self::Multiple(_)))
+                .unwrap_or(self),
+            _ => self,
+        }
+    }
+
+    /// Returns the first command error in the chain of an error, such as one with added context.
+    pub fn find(e: &anyhow::Error) -> Option<&Self> {
+        e.chain().find_map(|e| e.downcast_ref::<Self>())
+    }
+
+    /// Combine errors into one, returns `Ok` if there are none.
+    pub fn combine(mut errors: Vec<Self>) -> CommandResult<()> {
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(Self::Multiple(errors)),
+        }
+    }
+
+    /// Mark the error as already shown to the user, so that it is not replied with again.
+    pub fn shown(self) -> Self {
+        let e = match self {
+            Self::Other(e) => e,
+            e => anyhow::Error::new(e),
+        };
+
+        Self::Other(e.context(Shown))
+    }
+
+    /// Returns true if the error has already been shown to the user.
+    pub fn is_shown(&self) -> bool {
+        matches!(self, Self::Other(e) if e.downcast_ref::<Shown>().is_some())
+    }
+
+    /// Returns the id of the incident that the error has been reported as, if any.
+    pub fn incident(&self) -> Option<IncidentId> {
+        match self {
+            Self::Other(e) => e.downcast_ref::<IncidentId>().copied(),
+            _ => None,
+        }
+    }
+
+    /// Render a message for the user, with the `usage` of the command for argument errors.
+    /// Only `Other` errors are rendered as the generic message.
+    pub fn render(&self, usage: Option<&str>) -> String {
+        let with_usage = |text: String| match usage {
+            Some(usage) => format!("{text}\n```yaml\n{usage}\n```"),
+            None => text,
+        };
+
+        match self.user_error() {
+            Self::MissingArgs => with_usage("Some arguments are missing.".to_string()),
+            Self::ArgsMismatch => with_usage("Some arguments are of the wrong type.".to_string()),
+            Self::UnexpectedArgs(s) => with_usage(format!("Invalid arguments: {s}")),
+            Self::ParseError(s) => with_usage(format!("Could not parse arguments: {s}")),
+            Self::MissingReply => "Reply to a message to use this command.".to_string(),
+            Self::Disabled => "This command is not available here.".to_string(),
+            Self::AccessDenied => "Rekt, you cannot use that. :melting_face:".to_string(),
+            Self::Timeout => "You did not respond in time.".to_string(),
+            Self::Other(_) | Self::Multiple(_) => Self::GENERIC_MESSAGE.to_string(),
+            e => e.to_string(),
+        }
+    }
+}
+
+/// Context of an error that has already been shown to the user.
+#[derive(Debug, Clone, Copy)]
+struct Shown;
+
+impl std::fmt::Display for Shown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error shown to the user")
+    }
+}
+
 impl PartialEq for CommandError {
     fn eq(&self, other: &Self) -> bool {
         mem::discriminant(self) == mem::discriminant(other) // Close enough.
@@ -147,6 +264,7 @@ impl_into_command_error!(Other; twilight_http::Error);
 impl_into_command_error!(Other; twilight_http::response::DeserializeBodyError);
 impl_into_command_error!(Other; twilight_standby::future::Canceled);
 impl_into_command_error!(Other; twilight_util::builder::embed::image_source::ImageSourceUrlError);
+impl_into_command_error!(Other; twilight_validate::embed::EmbedValidationError);
 impl_into_command_error!(Other; twilight_validate::message::MessageValidationError);
 impl_into_command_error!(Other; twilight_validate::request::ValidationError);
 
@@ -156,12 +274,21 @@ pub trait ResponseFuture = Future<Output = CommandResponse> + Send;
 /// Trait alias for a command result future.
 pub trait CommandFuture = Future<Output = CommandResult<()>> + Send;
 
-/// Non-generic return type for async command functions.
+/// Trait alias for an autocomplete future.
+pub trait AutocompleteFuture = Future<Output = AutocompleteResponse> + Send;
+
+/// Non-generic return type for command responses.
 pub type CallFuture = Pin<Box<dyn CommandFuture>>;
 
+/// Non-generic return type for async command functions.
+pub type ResponseCallFuture = Pin<Box<dyn ResponseFuture>>;
+
 /// Response result from a command function.
 pub type CommandResponse = Result<Response, CommandError>;
 
+/// Suggested choices from an autocomplete function, as `(name, value)` pairs.
+pub type AutocompleteResponse = Result<Vec<(String, ArgValue)>, CommandError>;
+
 /// Generic command result with command error type.
 pub type CommandResult<T> = Result<T, CommandError>;
 
@@ -183,11 +310,25 @@ impl Response {
                 Request::Slash(req) => req.clear(&ctx).await,
                 Request::Message(req) => req.clear(&ctx).await,
                 Request::User(req) => req.clear(&ctx).await,
+                Request::Component(req) => req.clear(&ctx).await,
+                Request::Modal(req) => req.clear(&ctx).await,
             }
             .or(Ok(()))
         })
     }
 
+    /// Replies to the request with `reply`, such as text content or an embed.
+    pub fn reply(
+        ctx: Context,
+        req: impl Into<Request> + Send + 'static,
+        reply: impl Into<Reply> + Send + 'static,
+    ) -> Self {
+        Self::new(move || async move {
+            let req = req.into();
+            req.reply(&ctx, reply).await
+        })
+    }
+
     /// Creates a new response from a function.
     pub fn new<F, Fut>(f: F) -> Self
     where
@@ -209,9 +350,14 @@ impl Future for Response {
     }
 }
 
-/// Newtype for commands collection.
+/// Newtype for commands collection, along with the middleware run around their execution.
 #[derive(Debug, Default, Clone, IntoIterator, Index)]
-pub struct Commands(BTreeMap<&'static str, Arc<BaseCommand>>);
+pub struct Commands(
+    #[into_iterator(owned)]
+    #[index]
+    BTreeMap<&'static str, Arc<BaseCommand>>,
+    Middlewares,
+);
 
 impl Commands {
     /// Get base command by name.
@@ -227,15 +373,53 @@ impl Commands {
             .try_collect()
     }
 
+    /// Get the component handler that matches `custom_id` with the longest prefix,
+    /// along with the base command that it belongs to.
+    pub fn component(&self, custom_id: &str) -> Option<(&Arc<BaseCommand>, &ComponentHandler)> {
+        self.0
+            .values()
+            .flat_map(|b| b.components.iter().map(move |c| (b, c)))
+            .filter(|(_, c)| custom_id.starts_with(c.prefix))
+            .max_by_key(|(_, c)| c.prefix.len())
+    }
+
+    /// Returns paths of all commands, subcommands and groups, such as `voice play`.
+    pub fn paths(&self) -> Vec<String> {
+        self.0
+            .values()
+            .flat_map(|base| base.paths())
+            .map(|path| path.join(" "))
+            .collect()
+    }
+
     /// Get reference to the inner list.
     pub const fn inner(&self) -> &BTreeMap<&'static str, Arc<BaseCommand>> {
         &self.0
     }
+
+    /// Get the middleware run around command execution.
+    pub const fn middleware(&self) -> &Middlewares {
+        &self.1
+    }
 }
 
 impl std::fmt::Display for Commands {
+    /// Lists command names by category, one category per line.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.0.keys().collect::<Vec<_>>()) // TODO: Display nice list
+        for category in Category::ALL {
+            let names = self
+                .0
+                .values()
+                .filter(|b| b.category == category)
+                .map(|b| b.command.name)
+                .collect::<Vec<_>>();
+
+            if !names.is_empty() {
+                writeln!(f, "{}: {}", category.name(), names.join(", "))?;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -243,6 +427,7 @@ impl std::fmt::Display for Commands {
 #[derive(Debug, Default, Clone)]
 pub struct CommandsBuilder {
     list: Vec<BaseCommand>,
+    middleware: Middlewares,
 }
 
 impl CommandsBuilder {
@@ -257,9 +442,17 @@ impl CommandsBuilder {
         self
     }
 
+    /// Add a middleware to run around command execution.
+    /// Middleware is run in the order it was added, see `middleware` module.
+    pub fn wrap(&mut self, middleware: impl Middleware + 'static) -> &mut Self {
+        self.middleware.push(middleware);
+        self
+    }
+
     /// Validate the list of commands.
     pub fn validate(&self) -> AnyResult<()> {
         let mut set = HashSet::with_capacity(self.list.len());
+        let mut prefixes = HashSet::new();
 
         for cmd in self.list.iter() {
             // Ensure command itself is valid.
@@ -271,6 +464,15 @@ impl CommandsBuilder {
                 "Duplicate command found: {}",
                 cmd.command.name
             );
+
+            // Ensure component handlers don't overlap with others.
+            for handler in cmd.components.iter() {
+                anyhow::ensure!(
+                    prefixes.insert(handler.prefix),
+                    "Duplicate component prefix found: {}",
+                    handler.prefix
+                );
+            }
         }
 
         Ok(())
@@ -283,6 +485,7 @@ impl CommandsBuilder {
                 .into_iter()
                 .map(|b| (b.command.name, Arc::new(b)))
                 .collect(),
+            self.middleware,
         )
     }
 }
@@ -0,0 +1,200 @@
+//! Tracking of replies to classic commands.
+//!
+//! When a command message is edited, the command is executed again and its reply is edited instead of
+//! sending a new one, and when a command message is deleted, its reply is deleted too.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use twilight_model::id::marker::MessageMarker;
+use twilight_model::id::Id;
+
+/// Reply to a command message.
+#[derive(Debug, Clone, Copy)]
+struct Tracked {
+    reply_id: Id<MessageMarker>,
+    /// The command message was edited, and the reply is to be replaced.
+    stale: bool,
+    /// An edit of the command message was already executed without counting as a use.
+    rerun: bool,
+}
+
+/// Tracked replies, with the command messages in the order they were tracked.
+#[derive(Debug, Default)]
+struct Inner {
+    replies: HashMap<Id<MessageMarker>, Tracked>,
+    order: VecDeque<Id<MessageMarker>>,
+}
+
+/// Tracker for the replies to recent command messages.
+#[derive(Debug, Default)]
+pub struct Replies {
+    inner: Mutex<Inner>,
+}
+
+impl Replies {
+    /// Maximum number of tracked command messages, after which the oldest ones are forgotten.
+    const CAPACITY: usize = 1000;
+
+    /// Register the reply to a command message, unless it already has one.
+    pub fn insert(&self, message_id: Id<MessageMarker>, reply_id: Id<MessageMarker>) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.replies.contains_key(&message_id) {
+            return;
+        }
+
+        while inner.replies.len() >= Self::CAPACITY {
+            let Some(oldest) = inner.order.pop_front() else {
+                break;
+            };
+            inner.replies.remove(&oldest);
+        }
+
+        inner.replies.insert(message_id, Tracked {
+            reply_id,
+            stale: false,
+            rerun: false,
+        });
+        inner.order.push_back(message_id);
+    }
+
+    /// Mark the reply to an edited command message to be replaced by the next reply.
+    /// Returns false if the message has no tracked reply.
+    pub fn mark_stale(&self, message_id: Id<MessageMarker>) -> bool {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        match inner.replies.get_mut(&message_id) {
+            Some(tracked) => {
+                tracked.stale = true;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Returns true if the command message was edited and its reply has not been replaced yet.
+    pub fn is_stale(&self, message_id: Id<MessageMarker>) -> bool {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.replies.get(&message_id).map_or(false, |t| t.stale)
+    }
+
+    /// Returns true if the edited command message can be executed again without counting as a use.
+    /// Only the first edit of a command message is free, later edits count like new commands.
+    pub fn take_free_rerun(&self, message_id: Id<MessageMarker>) -> bool {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        match inner.replies.get_mut(&message_id) {
+            Some(tracked) if tracked.stale && !tracked.rerun => {
+                tracked.rerun = true;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns the stale reply that the next reply to a command message should edit, if any.
+    pub fn claim(&self, message_id: Id<MessageMarker>) -> Option<Id<MessageMarker>> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let tracked = inner.replies.get_mut(&message_id)?;
+        if !tracked.stale {
+            return None;
+        }
+
+        tracked.stale = false;
+        Some(tracked.reply_id)
+    }
+
+    /// Forget the reply to a command message if it is still stale, returning it.
+    pub fn take_stale(&self, message_id: Id<MessageMarker>) -> Option<Id<MessageMarker>> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if !inner.replies.get(&message_id)?.stale {
+            return None;
+        }
+
+        let tracked = inner.replies.remove(&message_id)?;
+        inner.order.retain(|id| *id != message_id);
+
+        Some(tracked.reply_id)
+    }
+
+    /// Forget a command message, returning its reply.
+    pub fn remove(&self, message_id: Id<MessageMarker>) -> Option<Id<MessageMarker>> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let tracked = inner.replies.remove(&message_id)?;
+        inner.order.retain(|id| *id != message_id);
+
+        Some(tracked.reply_id)
+    }
+
+    /// Forget the command message of a reply, such as when the reply is deleted.
+    pub fn remove_reply(&self, reply_id: Id<MessageMarker>) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(message_id) = inner
+            .replies
+            .iter()
+            .find(|(_, tracked)| tracked.reply_id == reply_id)
+            .map(|(id, _)| *id)
+        else {
+            return;
+        };
+
+        inner.replies.remove(&message_id);
+        inner.order.retain(|id| *id != message_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_stale_replies() {
+        let replies = Replies::default();
+        let (message, reply) = (Id::new(1), Id::new(2));
+
+        assert!(!replies.mark_stale(message));
+        replies.insert(message, reply);
+
+        // Only replies to edited messages are replaced.
+        assert_eq!(None, replies.claim(message));
+        assert!(!replies.is_stale(message));
+        assert!(replies.mark_stale(message));
+        assert!(replies.is_stale(message));
+        assert_eq!(Some(reply), replies.claim(message));
+        assert!(!replies.is_stale(message));
+        assert_eq!(None, replies.claim(message));
+        assert_eq!(None, replies.take_stale(message));
+
+        // Replies that were not replaced are taken once.
+        assert!(replies.mark_stale(message));
+        assert_eq!(Some(reply), replies.take_stale(message));
+        assert_eq!(None, replies.remove(message));
+    }
+
+    #[test]
+    fn one_free_rerun() {
+        let replies = Replies::default();
+        let (message, reply) = (Id::new(1), Id::new(2));
+        replies.insert(message, reply);
+
+        // Only the first edit is free.
+        assert!(!replies.take_free_rerun(message));
+        assert!(replies.mark_stale(message));
+        assert!(replies.take_free_rerun(message));
+        assert_eq!(Some(reply), replies.claim(message));
+        assert!(replies.mark_stale(message));
+        assert!(!replies.take_free_rerun(message));
+    }
+
+    #[test]
+    fn forget_oldest() {
+        let replies = Replies::default();
+        for id in 1..=Replies::CAPACITY as u64 + 1 {
+            replies.insert(Id::new(id), Id::new(id));
+        }
+
+        assert!(!replies.mark_stale(Id::new(1)));
+        assert!(replies.mark_stale(Id::new(2)));
+
+        replies.remove_reply(Id::new(2));
+        assert_eq!(None, replies.remove(Id::new(2)));
+    }
+}
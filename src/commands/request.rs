@@ -1,14 +1,30 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use derive_more::From;
-use twilight_model::application::interaction::application_command::CommandData;
-use twilight_model::application::interaction::Interaction;
+use twilight_gateway::Event;
+use twilight_http::error::ErrorType;
+use twilight_model::application::interaction::application_command::{
+    CommandData, InteractionMember,
+};
+use twilight_model::application::interaction::message_component::MessageComponentInteractionData;
+use twilight_model::application::interaction::modal::ModalInteractionData;
+use twilight_model::application::interaction::{Interaction, InteractionData};
+use twilight_model::channel::message::{Component, Embed, MessageFlags};
 use twilight_model::channel::Message;
-use twilight_model::id::marker::{MessageMarker, UserMarker};
+use twilight_model::http::attachment::Attachment;
+use twilight_model::http::interaction::{
+    InteractionResponse, InteractionResponseData, InteractionResponseType,
+};
+use twilight_model::id::marker::{ChannelMarker, GuildMarker, MessageMarker, UserMarker};
 use twilight_model::id::Id;
+use twilight_model::user::User;
+use twilight_util::builder::InteractionResponseDataBuilder;
 
-use crate::commands::arg::Args;
-use crate::commands::builder::BaseCommand;
+use crate::commands::arg::{Arg, ArgValue, Args};
+use crate::commands::builder::{BaseCommand, Modal};
+use crate::commands::middleware::Invocation;
+use crate::commands::{handle, CommandError, CommandResponse, CommandResult, Response};
 use crate::utils::prelude::*;
 use crate::Context;
 
@@ -37,6 +53,28 @@ impl ClassicRequest {
             .context("Failed to clear command message")
             .map(|_| ())
     }
+
+    /// Reply with the usage of all subcommands, for a command that was called without one.
+    pub async fn reply_usage(&self, ctx: &Context) -> CommandResponse {
+        let prefix = ctx
+            .config
+            .classic_prefix(self.message.guild_id)
+            .unwrap_or_default();
+
+        self.reply(ctx, usage_listing(&self.command, &prefix))
+            .await?;
+
+        Ok(Response::none())
+    }
+}
+
+/// Returns a message listing the usage of all subcommands of the command.
+fn usage_listing(command: &BaseCommand, prefix: &str) -> String {
+    format!(
+        "Usage of `{}`:\n```\n{}\n```",
+        command.command.name,
+        command.command.synopsis(prefix).join("\n")
+    )
 }
 
 /// Slash command request with preprocessed arguments and interaction data.
@@ -46,6 +84,7 @@ pub struct SlashRequest {
     pub interaction: Arc<Interaction>,
     pub data: Arc<CommandData>,
     pub args: Args,
+    replied: ReplyState,
 }
 
 impl SlashRequest {
@@ -56,6 +95,7 @@ impl SlashRequest {
         args: Args,
     ) -> Self {
         Self {
+            replied: ReplyState::default(),
             command,
             interaction,
             data,
@@ -63,6 +103,11 @@ impl SlashRequest {
         }
     }
 
+    /// Acknowledge the interaction with a publicly visible loading state message.
+    pub async fn defer(&self, ctx: &Context) -> CommandResult<()> {
+        defer_interaction(ctx, &self.interaction, &self.replied, false).await
+    }
+
     /// Deletes the interaction loading message (acknowledge response).
     pub async fn clear(&self, ctx: &Context) -> AnyResult<()> {
         ctx.interaction()
@@ -71,6 +116,24 @@ impl SlashRequest {
             .context("Failed to clear interaction")
             .map(|_| ())
     }
+
+    /// Reply with the usage of all subcommands, for a command that was called without one.
+    pub async fn reply_usage(&self, ctx: &Context) -> CommandResponse {
+        self.reply(ctx, usage_listing(&self.command, "/")).await?;
+
+        Ok(Response::none())
+    }
+
+    /// Opens a modal and waits for it to be submitted.
+    /// The command must be created with `no_defer(FunctionKind::Slash)`, as a modal can only be the first response.
+    pub async fn modal(
+        &self,
+        ctx: &Context,
+        modal: impl Into<Modal>,
+    ) -> CommandResult<ModalSubmit> {
+        self.replied.take(); // The modal is the response.
+        open_modal(ctx, &self.command, &self.interaction, modal.into()).await
+    }
 }
 
 /// Message command request with command and interaction data.
@@ -80,6 +143,7 @@ pub struct MessageRequest {
     pub interaction: Arc<Interaction>,
     pub data: Arc<CommandData>,
     pub target_id: Id<MessageMarker>,
+    replied: ReplyState,
 }
 
 impl MessageRequest {
@@ -90,6 +154,7 @@ impl MessageRequest {
         target_id: Id<MessageMarker>,
     ) -> Self {
         Self {
+            replied: ReplyState::default(),
             command,
             interaction,
             data,
@@ -97,6 +162,16 @@ impl MessageRequest {
         }
     }
 
+    /// Returns the target message, as resolved by Discord.
+    pub fn target(&self) -> Option<&Message> {
+        self.data.resolved.as_ref()?.messages.get(&self.target_id)
+    }
+
+    /// Acknowledge the interaction with a personal loading state message.
+    pub async fn defer(&self, ctx: &Context) -> CommandResult<()> {
+        defer_interaction(ctx, &self.interaction, &self.replied, true).await
+    }
+
     /// Deletes the interaction loading message (acknowledge response).
     pub async fn clear(&self, ctx: &Context) -> AnyResult<()> {
         ctx.interaction()
@@ -105,6 +180,17 @@ impl MessageRequest {
             .context("Failed to clear interaction")
             .map(|_| ())
     }
+
+    /// Opens a modal and waits for it to be submitted.
+    /// The command must be created with `no_defer(FunctionKind::Message)`, as a modal can only be the first response.
+    pub async fn modal(
+        &self,
+        ctx: &Context,
+        modal: impl Into<Modal>,
+    ) -> CommandResult<ModalSubmit> {
+        self.replied.take(); // The modal is the response.
+        open_modal(ctx, &self.command, &self.interaction, modal.into()).await
+    }
 }
 
 /// User command request with command and interaction data.
@@ -114,6 +200,7 @@ pub struct UserRequest {
     pub interaction: Arc<Interaction>,
     pub data: Arc<CommandData>,
     pub target_id: Id<UserMarker>,
+    replied: ReplyState,
 }
 
 impl UserRequest {
@@ -124,6 +211,7 @@ impl UserRequest {
         target_id: Id<UserMarker>,
     ) -> Self {
         Self {
+            replied: ReplyState::default(),
             command,
             interaction,
             data,
@@ -131,6 +219,21 @@ impl UserRequest {
         }
     }
 
+    /// Returns the target user, as resolved by Discord.
+    pub fn target(&self) -> Option<&User> {
+        self.data.resolved.as_ref()?.users.get(&self.target_id)
+    }
+
+    /// Returns the target guild member, as resolved by Discord.
+    pub fn target_member(&self) -> Option<&InteractionMember> {
+        self.data.resolved.as_ref()?.members.get(&self.target_id)
+    }
+
+    /// Acknowledge the interaction with a personal loading state message.
+    pub async fn defer(&self, ctx: &Context) -> CommandResult<()> {
+        defer_interaction(ctx, &self.interaction, &self.replied, true).await
+    }
+
     /// Deletes the interaction loading message (acknowledge response).
     pub async fn clear(&self, ctx: &Context) -> AnyResult<()> {
         ctx.interaction()
@@ -139,12 +242,732 @@ impl UserRequest {
             .context("Failed to clear interaction")
             .map(|_| ())
     }
+
+    /// Opens a modal and waits for it to be submitted.
+    /// The command must be created with `no_defer(FunctionKind::User)`, as a modal can only be the first response.
+    pub async fn modal(
+        &self,
+        ctx: &Context,
+        modal: impl Into<Modal>,
+    ) -> CommandResult<ModalSubmit> {
+        self.replied.take(); // The modal is the response.
+        open_modal(ctx, &self.command, &self.interaction, modal.into()).await
+    }
+}
+
+/// Message component request with interaction data.
+#[derive(Debug, Clone)]
+pub struct ComponentRequest {
+    pub command: Arc<BaseCommand>,
+    pub interaction: Arc<Interaction>,
+    pub data: Arc<MessageComponentInteractionData>,
+    /// The `custom_id` prefix that matched this request.
+    pub prefix: &'static str,
+    replied: ReplyState,
+}
+
+impl ComponentRequest {
+    pub fn new(
+        command: Arc<BaseCommand>,
+        interaction: Arc<Interaction>,
+        data: Arc<MessageComponentInteractionData>,
+        prefix: &'static str,
+    ) -> Self {
+        Self {
+            command,
+            interaction,
+            data,
+            prefix,
+            replied: ReplyState::default(),
+        }
+    }
+
+    /// Returns the rest of the `custom_id` after the matched prefix.
+    pub fn value(&self) -> &str {
+        self.data
+            .custom_id
+            .strip_prefix(self.prefix)
+            .unwrap_or(&self.data.custom_id)
+    }
+
+    /// Deletes the message that the component is attached to.
+    pub async fn clear(&self, ctx: &Context) -> AnyResult<()> {
+        ctx.interaction()
+            .delete_response(&self.interaction.token)
+            .await
+            .context("Failed to clear component message")
+            .map(|_| ())
+    }
+}
+
+/// Submitted modal with the values parsed as arguments.
+#[derive(Debug, Clone)]
+pub struct ModalSubmit {
+    pub command: Arc<BaseCommand>,
+    pub interaction: Arc<Interaction>,
+    pub data: Arc<ModalInteractionData>,
+    pub args: Args,
+    replied: ReplyState,
+}
+
+impl ModalSubmit {
+    /// How long to wait for the user to submit a modal.
+    pub const TIMEOUT: Duration = Duration::from_secs(600);
+
+    /// Deletes the submit loading message (acknowledge response).
+    pub async fn clear(&self, ctx: &Context) -> AnyResult<()> {
+        ctx.interaction()
+            .delete_response(&self.interaction.token)
+            .await
+            .context("Failed to clear modal submit")
+            .map(|_| ())
+    }
+}
+
+/// Responds to an interaction with a modal and waits for the submit.
+/// The submit is run through the middleware of the commands, and acknowledged with a personal loading state message,
+/// or answered with the error if it is rejected or the values are invalid.
+async fn open_modal(
+    ctx: &Context,
+    command: &Arc<BaseCommand>,
+    interaction: &Interaction,
+    modal: Modal,
+) -> CommandResult<ModalSubmit> {
+    modal.validate()?;
+
+    let custom_id = format!("modal:{}", interaction.id);
+
+    let resp = InteractionResponse {
+        kind: InteractionResponseType::Modal,
+        data: Some(
+            InteractionResponseDataBuilder::new()
+                .custom_id(&custom_id)
+                .title(&modal.title)
+                .components(modal.fields.iter().cloned().map(Into::into))
+                .build(),
+        ),
+    };
+
+    ctx.interaction()
+        .create_response(interaction.id, &interaction.token, &resp)
+        .await?;
+
+    // There is no event for a closed modal, so give up after a while.
+    let submit = {
+        let custom_id = custom_id.clone();
+        ctx.standby
+            .wait_for_event(move |event: &Event| match event {
+                Event::InteractionCreate(i) => match &i.data {
+                    Some(InteractionData::ModalSubmit(d)) => d.custom_id == custom_id,
+                    _ => false,
+                },
+                _ => false,
+            })
+    };
+
+    let Ok(event) = tokio::time::timeout(ModalSubmit::TIMEOUT, submit).await else {
+        return Err(CommandError::Timeout);
+    };
+
+    let Event::InteractionCreate(inter) = event? else {
+        return Err(anyhow::anyhow!("Standby returned an event that was not matched").into());
+    };
+
+    let mut inter = inter.0;
+    let Some(InteractionData::ModalSubmit(data)) = inter.data.take() else {
+        return Err(anyhow::anyhow!("Standby returned an interaction that was not matched").into());
+    };
+
+    let args = modal_args(&modal, &data);
+    let submit = ModalSubmit {
+        command: Arc::clone(command),
+        interaction: Arc::new(inter),
+        data: Arc::new(data),
+        args: args.as_ref().map_or_else(|_| Args::default(), Args::clone),
+        replied: ReplyState::default(),
+    };
+
+    // Validate before acknowledging, the original interaction cannot show the error.
+    let inv = Invocation::new(submit.clone().into(), &[command.command.name]);
+    let acknowledge = async {
+        args?;
+        defer_interaction(ctx, &submit.interaction, &submit.replied, true).await
+    };
+
+    if let Err(e) = handle::run_middleware(ctx, &inv, acknowledge).await {
+        let resp = InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(
+                InteractionResponseDataBuilder::new()
+                    .content(e.render(None))
+                    .flags(MessageFlags::EPHEMERAL)
+                    .build(),
+            ),
+        };
+
+        ctx.interaction()
+            .create_response(submit.interaction.id, &submit.interaction.token, &resp)
+            .await?;
+
+        return Err(e.shown());
+    }
+
+    Ok(submit)
+}
+
+/// Acknowledge the interaction with a loading state message, only visible to the user if `ephemeral`,
+/// and record the deferral so that replies keep its visibility.
+async fn defer_interaction(
+    ctx: &Context,
+    interaction: &Interaction,
+    replied: &ReplyState,
+    ephemeral: bool,
+) -> CommandResult<()> {
+    let resp = replied.defer(ephemeral);
+
+    ctx.interaction()
+        .create_response(interaction.id, &interaction.token, &resp)
+        .await?;
+
+    Ok(())
+}
+
+/// Parse the submitted values of a modal the same way as classic arguments.
+fn modal_args(modal: &Modal, data: &ModalInteractionData) -> CommandResult<Args> {
+    let mut args = Vec::with_capacity(modal.fields.len());
+    for field in modal.fields.iter() {
+        let value = data
+            .components
+            .iter()
+            .flat_map(|row| row.components.iter())
+            .find(|c| c.custom_id == field.arg.name)
+            .and_then(|c| c.value.as_deref())
+            .filter(|v| !v.is_empty());
+
+        let Some(value) = value else {
+            if field.arg.required {
+                return Err(CommandError::MissingArgs);
+            }
+            continue;
+        };
+
+        let value = ArgValue::from_kind(&field.arg.kind, value).map_err(|e| {
+            CommandError::UnexpectedArgs(format!(
+                "Expected an argument '{}' of type '{}': {e}",
+                field.arg.name, field.arg.kind
+            ))
+        })?;
+
+        value.check(&field.arg.kind).map_err(|e| {
+            CommandError::UnexpectedArgs(format!("Argument '{}' {e}", field.arg.name))
+        })?;
+
+        args.push(Arg {
+            name: field.arg.name.to_string(),
+            value,
+        });
+    }
+
+    Ok(Args::from(args))
+}
+
+/// Autocomplete request with interaction data and the focused argument.
+#[derive(Debug, Clone)]
+pub struct AutocompleteRequest {
+    pub command: Arc<BaseCommand>,
+    pub interaction: Arc<Interaction>,
+    pub data: Arc<CommandData>,
+    /// Other arguments that have been filled so far.
+    pub args: Args,
+    /// Name of the focused argument.
+    pub focused: String,
+    /// Current (partial) input of the focused argument.
+    pub input: String,
+}
+
+impl AutocompleteRequest {
+    pub fn new(
+        command: Arc<BaseCommand>,
+        interaction: Arc<Interaction>,
+        data: Arc<CommandData>,
+        args: Args,
+        focused: String,
+        input: String,
+    ) -> Self {
+        Self {
+            command,
+            interaction,
+            data,
+            args,
+            focused,
+            input,
+        }
+    }
 }
 
-#[derive(Debug, From)]
+#[derive(Debug, Clone, From)]
 pub enum Request {
     Classic(ClassicRequest),
     Slash(SlashRequest),
     Message(MessageRequest),
     User(UserRequest),
+    Component(ComponentRequest),
+    Modal(ModalSubmit),
+}
+
+impl Request {
+    /// Returns the base command of the request.
+    pub fn command(&self) -> &Arc<BaseCommand> {
+        match self {
+            Self::Classic(req) => &req.command,
+            Self::Slash(req) => &req.command,
+            Self::Message(req) => &req.command,
+            Self::User(req) => &req.command,
+            Self::Component(req) => &req.command,
+            Self::Modal(req) => &req.command,
+        }
+    }
+
+    /// Returns the interaction of the request, `None` for classic commands.
+    pub fn interaction(&self) -> Option<&Interaction> {
+        match self {
+            Self::Classic(_) => None,
+            Self::Slash(req) => Some(&req.interaction),
+            Self::Message(req) => Some(&req.interaction),
+            Self::User(req) => Some(&req.interaction),
+            Self::Component(req) => Some(&req.interaction),
+            Self::Modal(req) => Some(&req.interaction),
+        }
+    }
+
+    /// Returns the user who made the request.
+    pub fn user_id(&self) -> Option<Id<UserMarker>> {
+        match self {
+            Self::Classic(req) => Some(req.message.author.id),
+            req => req.interaction().and_then(Interaction::author_id),
+        }
+    }
+
+    /// Returns the channel where the request was made.
+    pub fn channel_id(&self) -> Option<Id<ChannelMarker>> {
+        match self {
+            Self::Classic(req) => Some(req.message.channel_id),
+            req => req
+                .interaction()
+                .and_then(|inter| inter.channel.as_ref().map(|c| c.id)),
+        }
+    }
+
+    /// Returns the guild where the request was made, if any.
+    pub fn guild_id(&self) -> Option<Id<GuildMarker>> {
+        match self {
+            Self::Classic(req) => req.message.guild_id,
+            req => req.interaction().and_then(|inter| inter.guild_id),
+        }
+    }
+
+    /// Reply to the request, see `Responder::reply`.
+    pub async fn reply(&self, ctx: &Context, reply: impl Into<Reply> + Send) -> CommandResult<()> {
+        match self {
+            Self::Classic(req) => req.reply(ctx, reply).await,
+            Self::Slash(req) => req.reply(ctx, reply).await,
+            Self::Message(req) => req.reply(ctx, reply).await,
+            Self::User(req) => req.reply(ctx, reply).await,
+            Self::Component(req) => req.reply(ctx, reply).await,
+            Self::Modal(req) => req.reply(ctx, reply).await,
+        }
+    }
+}
+
+/// Message content of a reply to a request.
+#[derive(Debug, Clone, Default)]
+pub struct Reply {
+    pub content: Option<String>,
+    pub embeds: Vec<Embed>,
+    pub components: Vec<Component>,
+    pub attachments: Vec<Attachment>,
+    /// Only the user who used the command can see the reply.
+    pub ephemeral: bool,
+}
+
+impl Reply {
+    /// Create an empty reply.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the text content.
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.content = Some(content.into());
+        self
+    }
+
+    /// Add an embed.
+    pub fn embed(mut self, embed: Embed) -> Self {
+        self.embeds.push(embed);
+        self
+    }
+
+    /// Set the message components, such as action rows of buttons.
+    pub fn components(mut self, components: Vec<Component>) -> Self {
+        self.components = components;
+        self
+    }
+
+    /// Add a file attachment.
+    pub fn attachment(mut self, attachment: Attachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
+    /// Show the reply only to the user who used the command.
+    pub const fn ephemeral(mut self) -> Self {
+        self.ephemeral = true;
+        self
+    }
+
+    /// Returns the interaction message flags.
+    fn flags(&self) -> MessageFlags {
+        if self.ephemeral {
+            MessageFlags::EPHEMERAL
+        } else {
+            MessageFlags::empty()
+        }
+    }
+}
+
+impl From<String> for Reply {
+    fn from(content: String) -> Self {
+        Self::new().content(content)
+    }
+}
+
+impl From<&str> for Reply {
+    fn from(content: &str) -> Self {
+        Self::new().content(content)
+    }
+}
+
+impl From<Embed> for Reply {
+    fn from(embed: Embed) -> Self {
+        Self::new().embed(embed)
+    }
+}
+
+/// A request that can be replied to.
+#[async_trait]
+pub trait Responder {
+    /// Reply to the request with the transport that fits it:
+    /// - Classic requests reply to the command message,
+    ///   or send a DM if the channel is not writable or the reply is ephemeral.
+    /// - Interactions get a response, or the deferred response is updated.
+    ///   If the visibility of the reply differs from the loading message,
+    ///   the loading message is deleted and the reply is sent as a followup instead.
+    /// - Any later replies to interactions are sent as followups.
+    async fn reply<R>(&self, ctx: &Context, reply: R) -> CommandResult<()>
+    where
+        R: Into<Reply> + Send;
+}
+
+#[async_trait]
+impl Responder for ClassicRequest {
+    async fn reply<R>(&self, ctx: &Context, reply: R) -> CommandResult<()>
+    where
+        R: Into<Reply> + Send,
+    {
+        reply_to_message(ctx, &self.message, reply.into()).await
+    }
+}
+
+/// Reply to a command message, or send the reply to its author if it is ephemeral or replying is not allowed.
+/// If the command message was edited, its previous reply is edited instead.
+pub async fn reply_to_message(ctx: &Context, message: &Message, reply: Reply) -> CommandResult<()> {
+    let author_id = message.author.id;
+
+    if !reply.ephemeral {
+        let result = match ctx.replies.claim(message.id) {
+            Some(reply_id) => update_message(ctx, message.channel_id, reply_id, &reply).await,
+            None => create_message(ctx, message.channel_id, Some(message.id), &reply)
+                .await
+                .map(|sent| ctx.replies.insert(message.id, sent.id)),
+        };
+
+        match result {
+            Err(e) if is_forbidden(&e) => {
+                debug!(
+                    "Cannot reply in channel '{}'; Sending to user '{author_id}' instead",
+                    message.channel_id
+                );
+            },
+            result => return result,
+        }
+    }
+
+    let channel = ctx.http.create_private_channel(author_id).send().await?;
+    create_message(ctx, channel.id, None, &reply).await?;
+
+    Ok(())
+}
+
+/// Implements `Responder` for interaction requests.
+macro impl_interaction_responder($($req:ty),* $(,)?) {
+    $(
+        #[async_trait]
+        impl Responder for $req {
+            async fn reply<R>(&self, ctx: &Context, reply: R) -> CommandResult<()>
+            where
+                R: Into<Reply> + Send,
+            {
+                interaction_reply(ctx, &self.interaction, &self.replied, &reply.into()).await
+            }
+        }
+    )*
+}
+
+impl_interaction_responder!(
+    SlashRequest,
+    MessageRequest,
+    UserRequest,
+    ComponentRequest,
+    ModalSubmit,
+);
+
+/// Progress of the response to an interaction, shared between clones of the request.
+#[derive(Debug, Clone, Default)]
+struct ReplyState(Arc<Mutex<Progress>>);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Progress {
+    /// Nothing has been sent yet.
+    #[default]
+    Pending,
+    /// A loading message was sent, only visible to the user if `ephemeral`.
+    Deferred { ephemeral: bool },
+    /// A response was sent.
+    Replied,
+}
+
+impl Progress {
+    /// Returns how a reply is sent in this state.
+    const fn transport(self, reply_ephemeral: bool) -> Transport {
+        match self {
+            Self::Pending => Transport::Respond,
+            Self::Deferred { ephemeral } if ephemeral == reply_ephemeral => Transport::Update,
+            Self::Deferred { .. } => Transport::Replace,
+            Self::Replied => Transport::Followup,
+        }
+    }
+}
+
+/// How a reply is sent to an interaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transport {
+    /// Send the initial response.
+    Respond,
+    /// Update the deferred response.
+    Update,
+    /// Delete the deferred response and send a followup, which can have a different visibility.
+    Replace,
+    /// Send a followup message.
+    Followup,
+}
+
+impl ReplyState {
+    /// Mark the interaction as deferred, returning the loading state response with the same visibility.
+    fn defer(&self, ephemeral: bool) -> InteractionResponse {
+        let mut progress = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        *progress = Progress::Deferred { ephemeral };
+
+        InteractionResponse {
+            kind: InteractionResponseType::DeferredChannelMessageWithSource,
+            data: ephemeral.then(|| InteractionResponseData {
+                flags: Some(MessageFlags::EPHEMERAL | MessageFlags::LOADING),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Mark the interaction as responded, returning the previous progress.
+    fn take(&self) -> Progress {
+        let mut progress = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::replace(&mut *progress, Progress::Replied)
+    }
+}
+
+/// Send a message to a channel, optionally as a reply to a message.
+async fn create_message(
+    ctx: &Context,
+    channel_id: Id<ChannelMarker>,
+    reply_to: Option<Id<MessageMarker>>,
+    reply: &Reply,
+) -> CommandResult<Message> {
+    let mut req = ctx
+        .http
+        .create_message(channel_id)
+        .embeds(&reply.embeds)?
+        .components(&reply.components)?
+        .attachments(&reply.attachments)?;
+    if let Some(content) = &reply.content {
+        req = req.content(content)?;
+    }
+    if let Some(message_id) = reply_to {
+        req = req.reply(message_id);
+    }
+
+    Ok(req.send().await?)
+}
+
+/// Replace the contents of a previously sent message with a reply.
+async fn update_message(
+    ctx: &Context,
+    channel_id: Id<ChannelMarker>,
+    message_id: Id<MessageMarker>,
+    reply: &Reply,
+) -> CommandResult<()> {
+    ctx.http
+        .update_message(channel_id, message_id)
+        .content(reply.content.as_deref())?
+        .embeds(Some(&reply.embeds))?
+        .components(Some(&reply.components))?
+        .attachments(&reply.attachments)?
+        .await?;
+
+    Ok(())
+}
+
+/// Respond to an interaction, update the deferred response or send a followup.
+async fn interaction_reply(
+    ctx: &Context,
+    interaction: &Interaction,
+    state: &ReplyState,
+    reply: &Reply,
+) -> CommandResult<()> {
+    let client = ctx.interaction();
+
+    let transport = state.take().transport(reply.ephemeral);
+    match transport {
+        Transport::Respond => {
+            let mut data = InteractionResponseDataBuilder::new()
+                .embeds(reply.embeds.iter().cloned())
+                .components(reply.components.iter().cloned())
+                .attachments(reply.attachments.iter().cloned())
+                .flags(reply.flags());
+            if let Some(content) = &reply.content {
+                data = data.content(content);
+            }
+
+            let resp = InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(data.build()),
+            };
+            client
+                .create_response(interaction.id, &interaction.token, &resp)
+                .await?;
+        },
+        Transport::Update => {
+            client
+                .update_response(&interaction.token)
+                .content(reply.content.as_deref())?
+                .embeds(Some(&reply.embeds))?
+                .components(Some(&reply.components))?
+                .attachments(&reply.attachments)?
+                .await?;
+        },
+        Transport::Replace | Transport::Followup => {
+            if transport == Transport::Replace {
+                client.delete_response(&interaction.token).await?;
+            }
+
+            let mut req = client
+                .create_followup(&interaction.token)
+                .embeds(&reply.embeds)?
+                .components(&reply.components)?
+                .attachments(&reply.attachments)?
+                .flags(reply.flags());
+            if let Some(content) = &reply.content {
+                req = req.content(content)?;
+            }
+            req.await?;
+        },
+    }
+
+    Ok(())
+}
+
+/// Returns true if the error is a response of missing access or permissions.
+fn is_forbidden(e: &CommandError) -> bool {
+    let CommandError::Other(e) = e else {
+        return false;
+    };
+
+    e.downcast_ref::<twilight_http::Error>()
+        .map_or(false, |e| match e.kind() {
+            ErrorType::Response { status, .. } => status.get() == 403,
+            _ => false,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interaction_transport() {
+        assert_eq!(Transport::Respond, Progress::Pending.transport(true));
+
+        // The visibility of the loading message cannot be changed by updating it.
+        let public = Progress::Deferred { ephemeral: false };
+        assert_eq!(Transport::Update, public.transport(false));
+        assert_eq!(Transport::Replace, public.transport(true));
+
+        let personal = Progress::Deferred { ephemeral: true };
+        assert_eq!(Transport::Update, personal.transport(true));
+        assert_eq!(Transport::Replace, personal.transport(false));
+
+        assert_eq!(Transport::Followup, Progress::Replied.transport(false));
+    }
+
+    #[test]
+    fn modal_constraints() {
+        use crate::commands::builder::*;
+
+        let modal: Modal = modal("Mute")
+            .field(integer("seconds", "Duration of the mute.").min(0))
+            .into();
+        let submit = |value: &str| -> ModalInteractionData {
+            serde_json::from_value(serde_json::json!({
+                "custom_id": "modal",
+                "components": [{
+                    "components": [{ "custom_id": "seconds", "type": 4, "value": value }],
+                }],
+            }))
+            .unwrap()
+        };
+
+        let args = modal_args(&modal, &submit("5")).unwrap();
+        assert_eq!(5, args.integer("seconds").unwrap());
+
+        // Values are checked against the same constraints as other arguments.
+        assert!(matches!(
+            modal_args(&modal, &submit("-5")),
+            Err(CommandError::UnexpectedArgs(_))
+        ));
+    }
+
+    #[test]
+    fn deferred_visibility() {
+        for ephemeral in [false, true] {
+            let state = ReplyState::default();
+            let resp = state.defer(ephemeral);
+
+            // The recorded state has the visibility of the loading message that is sent.
+            let flags = resp.data.and_then(|d| d.flags);
+            assert_eq!(
+                ephemeral,
+                flags.map_or(false, |f| f.contains(MessageFlags::EPHEMERAL))
+            );
+            assert_eq!(Progress::Deferred { ephemeral }, state.take());
+            assert_eq!(Progress::Replied, state.take());
+        }
+    }
 }
@@ -0,0 +1,293 @@
+//! Application command registration.
+//!
+//! Registered commands are compared against the local commands,
+//! so that only new, changed or removed commands are sent to Discord.
+
+use std::env;
+
+use twilight_model::application::command::{Command, CommandType};
+use twilight_model::id::marker::GuildMarker;
+use twilight_model::id::Id;
+
+use crate::commands::builder::twilight::commands_eq;
+use crate::utils::prelude::*;
+use crate::Context;
+
+/// Environment variable for a guild where commands are registered instead of globally.
+/// Guild commands are updated instantly, which is useful for development.
+pub const DEV_GUILD_VAR: &str = "DISCORD_DEV_GUILD";
+
+/// Environment variable for a guild where owner only commands are registered.
+/// Defaults to the development guild, owner commands are not registered if neither is set.
+pub const OWNER_GUILD_VAR: &str = "DISCORD_OWNER_GUILD";
+
+/// Changes needed to bring registered commands up to date.
+#[derive(Debug, Default)]
+pub struct CommandsDiff {
+    /// Commands that are not registered yet.
+    pub create: Vec<Command>,
+    /// Commands that are registered, but differ from the local ones.
+    pub update: Vec<Command>,
+    /// Registered commands that no longer exist locally.
+    pub delete: Vec<Command>,
+}
+
+impl CommandsDiff {
+    /// Compare `registered` commands to `local` commands.
+    /// Commands are matched by their name and kind.
+    pub fn new(mut registered: Vec<Command>, local: Vec<Command>) -> Self {
+        let mut diff = Self::default();
+
+        for cmd in local {
+            match registered
+                .iter()
+                .position(|r| r.name == cmd.name && r.kind == cmd.kind)
+            {
+                Some(idx) => {
+                    if !commands_eq(&registered.swap_remove(idx), &cmd) {
+                        diff.update.push(cmd);
+                    }
+                },
+                None => diff.create.push(cmd),
+            }
+        }
+
+        diff.delete = registered;
+        diff
+    }
+
+    /// Returns true if there is nothing to change.
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.update.is_empty() && self.delete.is_empty()
+    }
+}
+
+/// Returns the guild id set in the environment variable `var`, if any.
+fn guild_var(var: &str) -> AnyResult<Option<Id<GuildMarker>>> {
+    env::var(var)
+        .ok()
+        .map(|id| id.parse())
+        .transpose()
+        .with_context(|| format!("Invalid guild id in '{var}'"))
+}
+
+/// Returns the development guild id, if set.
+pub fn dev_guild() -> AnyResult<Option<Id<GuildMarker>>> {
+    guild_var(DEV_GUILD_VAR)
+}
+
+/// Returns the owner guild id, if set, or otherwise the development guild id.
+pub fn owner_guild() -> AnyResult<Option<Id<GuildMarker>>> {
+    Ok(guild_var(OWNER_GUILD_VAR)?.or(dev_guild()?))
+}
+
+/// Register application commands globally or to the development guild,
+/// and owner commands to the owner guild,
+/// changing only what differs from the already registered commands.
+/// Global commands are removed when using the development guild, so that they are not listed twice.
+pub async fn register(ctx: &Context) -> AnyResult<()> {
+    let guild_id = dev_guild()?;
+    let owner_guild_id = owner_guild()?;
+
+    let (mut owner, mut public): (Vec<_>, Vec<_>) = ctx
+        .commands
+        .twilight_commands()?
+        .into_iter()
+        .partition(|cmd| ctx.commands.get(&cmd.name).map_or(false, |b| b.owner_only));
+
+    match owner_guild_id {
+        // Both lists are registered to the same guild at once,
+        // otherwise registering one would remove the other.
+        Some(id) if Some(id) == guild_id => {
+            public.append(&mut owner);
+        },
+        Some(id) => register_to(ctx, Some(id), owner).await?,
+        None if !owner.is_empty() => {
+            debug!("No owner guild set, owner commands are only available as classic commands");
+        },
+        None => {},
+    }
+
+    if guild_id.is_some() {
+        clear_global(ctx).await?;
+    }
+
+    register_to(ctx, guild_id, public).await
+}
+
+/// Remove all global commands, if any are registered.
+async fn clear_global(ctx: &Context) -> AnyResult<()> {
+    let client = ctx.interaction();
+    let registered = client.global_commands().send().await?;
+    if registered.is_empty() {
+        return Ok(());
+    }
+
+    info!("Removing {} global application commands", registered.len());
+    client
+        .set_global_commands(&[])
+        .await
+        .context("Failed to remove global commands")?;
+
+    Ok(())
+}
+
+/// Register `local` commands globally or to a guild,
+/// changing only what differs from the already registered commands.
+async fn register_to(
+    ctx: &Context,
+    guild_id: Option<Id<GuildMarker>>,
+    mut local: Vec<Command>,
+) -> AnyResult<()> {
+    let client = ctx.interaction();
+
+    let registered = match guild_id {
+        Some(guild_id) => {
+            // Guild commands cannot be used in DMs anyway.
+            for cmd in local.iter_mut() {
+                cmd.dm_permission = None;
+            }
+            client.guild_commands(guild_id).send().await?
+        },
+        None => client.global_commands().send().await?,
+    };
+
+    let diff = CommandsDiff::new(registered, local);
+
+    if diff.is_empty() {
+        debug!("Application commands are up to date ({})", scope(guild_id));
+        return Ok(());
+    }
+
+    info!(
+        "Registering application commands ({}): {} new, {} changed, {} removed",
+        scope(guild_id),
+        diff.create.len(),
+        diff.update.len(),
+        diff.delete.len()
+    );
+
+    // Creating a command with an existing name overwrites it.
+    for cmd in diff.create.iter().chain(diff.update.iter()) {
+        create(ctx, guild_id, cmd)
+            .await
+            .with_context(|| format!("Failed to register command '{}'", cmd.name))?;
+    }
+
+    for cmd in diff.delete.iter() {
+        let Some(id) = cmd.id else {
+            continue;
+        };
+
+        match guild_id {
+            Some(guild_id) => client.delete_guild_command(guild_id, id).await?,
+            None => client.delete_global_command(id).await?,
+        };
+    }
+
+    Ok(())
+}
+
+/// Returns a description of where commands are registered.
+fn scope(guild_id: Option<Id<GuildMarker>>) -> String {
+    guild_id.map_or_else(|| "global".to_string(), |id| format!("guild '{id}'"))
+}
+
+/// Sends a create command request with the default member permissions applied.
+macro send($req:expr, $cmd:expr) {{
+    let mut req = $req;
+    if let Some(permissions) = $cmd.default_member_permissions {
+        req = req.default_member_permissions(permissions);
+    }
+    req.await?;
+}}
+
+/// Create or overwrite a command globally or in a guild.
+async fn create(ctx: &Context, guild_id: Option<Id<GuildMarker>>, cmd: &Command) -> AnyResult<()> {
+    let client = ctx.interaction();
+    let dm = cmd.dm_permission.unwrap_or(true);
+
+    match (guild_id, cmd.kind) {
+        (None, CommandType::ChatInput) => send!(
+            client
+                .create_global_command()
+                .chat_input(&cmd.name, &cmd.description)?
+                .command_options(&cmd.options)?
+                .dm_permission(dm),
+            cmd
+        ),
+        (None, CommandType::Message) => send!(
+            client
+                .create_global_command()
+                .message(&cmd.name)?
+                .dm_permission(dm),
+            cmd
+        ),
+        (None, CommandType::User) => send!(
+            client
+                .create_global_command()
+                .user(&cmd.name)?
+                .dm_permission(dm),
+            cmd
+        ),
+        (Some(guild_id), CommandType::ChatInput) => send!(
+            client
+                .create_guild_command(guild_id)
+                .chat_input(&cmd.name, &cmd.description)?
+                .command_options(&cmd.options)?,
+            cmd
+        ),
+        (Some(guild_id), CommandType::Message) => send!(
+            client.create_guild_command(guild_id).message(&cmd.name)?,
+            cmd
+        ),
+        (Some(guild_id), CommandType::User) => {
+            send!(client.create_guild_command(guild_id).user(&cmd.name)?, cmd)
+        },
+        (_, kind) => anyhow::bail!("Unsupported command kind: {kind:?}"),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_util::builder::command::{CommandBuilder, StringBuilder};
+
+    use super::*;
+
+    fn command(name: &str, description: &str) -> Command {
+        CommandBuilder::new(name, description, CommandType::ChatInput)
+            .option(StringBuilder::new("text", "Some text.").required(false))
+            .build()
+    }
+
+    #[test]
+    fn diff_commands() {
+        let registered = |name, description| {
+            let mut cmd = command(name, description);
+            cmd.id = Some(Id::new(1));
+            cmd.version = Id::new(2);
+            cmd.options[0].required = None; // Discord leaves out defaults.
+            cmd
+        };
+
+        let diff = CommandsDiff::new(
+            vec![
+                registered("same", "description"),
+                registered("changed", "description"),
+                registered("removed", "description"),
+            ],
+            vec![
+                command("same", "description"),
+                command("changed", "new description"),
+                command("new", "description"),
+            ],
+        );
+
+        let names = |list: &[Command]| list.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+        assert_eq!(vec!["new"], names(&diff.create));
+        assert_eq!(vec!["changed"], names(&diff.update));
+        assert_eq!(vec!["removed"], names(&diff.delete));
+    }
+}
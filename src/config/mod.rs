@@ -9,12 +9,14 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use twilight_model::channel::message::ReactionType;
-use twilight_model::id::marker::{ChannelMarker, GuildMarker, MessageMarker, RoleMarker};
+use twilight_model::id::marker::{
+    ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker,
+};
 use twilight_model::id::Id;
 
 use crate::config::storage::{Directory, Storage};
-use crate::utils;
 use crate::utils::prelude::*;
+use crate::{parser, utils};
 
 pub mod storage;
 
@@ -36,6 +38,14 @@ pub struct GlobalSettings {
     #[serde(default)]
     pub prefix: Prefix,
 
+    /// Additional global classic command prefixes.
+    #[serde(default)]
+    pub prefixes: Vec<Prefix>,
+
+    /// If global prefixes are matched case-insensitively.
+    #[serde(default)]
+    pub prefix_ignore_case: bool,
+
     /// Whitelisted guilds, disabled if `None`.
     #[serde(default)]
     pub whitelist: Option<Whitelist>,
@@ -48,13 +58,169 @@ pub struct GuildSettings {
     #[serde(default)]
     pub prefix: Prefix,
 
-    // TODO: To be implemented.
+    /// Additional guild specific classic command prefixes.
+    #[serde(default)]
+    pub prefixes: Vec<Prefix>,
+
+    /// If guild prefixes are matched case-insensitively.
+    #[serde(default)]
+    pub prefix_ignore_case: bool,
+
+    /// Classic command aliases, mapped from alias name to a command with preset arguments.
     #[serde(default)]
     pub aliases: HashMap<String, String>,
 
     /// Guild reaction-role mappings.
     #[serde(default)]
     pub reaction_roles: HashMap<String, Vec<ReactionRole>>,
+
+    /// Command restrictions, mapped by command path, such as `voice play`.
+    #[serde(default)]
+    pub commands: HashMap<String, CommandSettings>,
+
+    /// Command permission overrides, mapped by command path, such as `voice play`.
+    #[serde(default)]
+    pub permissions: HashMap<String, PermissionOverrides>,
+
+    /// Application command permissions mirrored from Discord, mapped by command name.
+    /// Permissions of all commands are mapped by an empty name.
+    #[serde(default)]
+    pub synced_permissions: HashMap<String, PermissionOverrides>,
+}
+
+impl GuildSettings {
+    /// Returns true if the user is granted or false if denied the command path by permission overrides,
+    /// or `None` if not overridden. Overrides of a subcommand take precedence over its parents,
+    /// and the configured overrides over the ones synced from Discord.
+    pub fn permission_override(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        roles: &[Id<RoleMarker>],
+        path: &[&str],
+    ) -> Option<bool> {
+        let resolve = |overrides: &HashMap<String, PermissionOverrides>| {
+            (0..=path.len()).rev().find_map(|n| {
+                overrides
+                    .get(&path[..n].join(" "))?
+                    .resolve(guild_id, user_id, roles)
+            })
+        };
+
+        resolve(&self.permissions).or_else(|| resolve(&self.synced_permissions))
+    }
+}
+
+/// Guild specific command restrictions.
+/// Restrictions of a command also apply to its subcommands.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandSettings {
+    /// If the command cannot be used at all.
+    #[serde(default)]
+    pub disabled: bool,
+
+    /// Channels where the command can be used, any channel if empty.
+    #[serde(default)]
+    pub allow_channels: HashSet<Id<ChannelMarker>>,
+
+    /// Channels where the command cannot be used.
+    #[serde(default)]
+    pub deny_channels: HashSet<Id<ChannelMarker>>,
+
+    /// Roles that can use the command, any role if empty.
+    #[serde(default)]
+    pub allow_roles: HashSet<Id<RoleMarker>>,
+
+    /// Roles that cannot use the command.
+    #[serde(default)]
+    pub deny_roles: HashSet<Id<RoleMarker>>,
+}
+
+impl CommandSettings {
+    /// Returns true if a member with `roles` can use the command in the channel.
+    pub fn allows(&self, channel_id: Option<Id<ChannelMarker>>, roles: &[Id<RoleMarker>]) -> bool {
+        if self.disabled {
+            return false;
+        }
+
+        let channel_allowed = channel_id.map_or(true, |id| {
+            !self.deny_channels.contains(&id)
+                && (self.allow_channels.is_empty() || self.allow_channels.contains(&id))
+        });
+
+        let roles_allowed = !roles.iter().any(|id| self.deny_roles.contains(id))
+            && (self.allow_roles.is_empty()
+                || roles.iter().any(|id| self.allow_roles.contains(id)));
+
+        channel_allowed && roles_allowed
+    }
+
+    /// Returns true if there are no restrictions.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Guild specific permission overrides of a command,
+/// which grant or deny the command regardless of the member's permissions.
+/// Overrides of a command also apply to its subcommands.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionOverrides {
+    /// Users that are granted the command.
+    #[serde(default)]
+    pub allow_users: HashSet<Id<UserMarker>>,
+
+    /// Users that are denied the command.
+    #[serde(default)]
+    pub deny_users: HashSet<Id<UserMarker>>,
+
+    /// Roles that are granted the command.
+    #[serde(default)]
+    pub allow_roles: HashSet<Id<RoleMarker>>,
+
+    /// Roles that are denied the command.
+    #[serde(default)]
+    pub deny_roles: HashSet<Id<RoleMarker>>,
+}
+
+impl PermissionOverrides {
+    /// Returns true if a member with `roles` is granted or false if denied the command,
+    /// or `None` if not overridden. Users take precedence over roles, and allowed roles over denied ones.
+    /// The `@everyone` role of the guild applies only if none of the member's roles are overridden.
+    pub fn resolve(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        roles: &[Id<RoleMarker>],
+    ) -> Option<bool> {
+        if self.deny_users.contains(&user_id) {
+            return Some(false);
+        }
+        if self.allow_users.contains(&user_id) {
+            return Some(true);
+        }
+        if roles.iter().any(|id| self.allow_roles.contains(id)) {
+            return Some(true);
+        }
+        if roles.iter().any(|id| self.deny_roles.contains(id)) {
+            return Some(false);
+        }
+
+        // `@everyone` role id is the same as the guild's id.
+        let everyone_id = guild_id.cast();
+        if self.allow_roles.contains(&everyone_id) {
+            Some(true)
+        } else if self.deny_roles.contains(&everyone_id) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if there are no overrides.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
 }
 
 #[derive(Debug)]
@@ -132,6 +298,20 @@ impl BotConfig {
         guild_id.map_or_else(global_prefix, guild_prefix)
     }
 
+    /// Return all classic command prefixes, either global prefixes or guild specific ones.
+    pub fn classic_prefixes(&self, guild_id: Option<Id<GuildMarker>>) -> AnyResult<Prefixes> {
+        let global_prefixes = || self.global().classic_prefixes();
+
+        let guild_prefixes = |guild_id| {
+            self.guild(guild_id)
+                .classic_prefixes()
+                .map_err(|e| debug!("{e}"))
+                .or_else(|_| global_prefixes())
+        };
+
+        guild_id.map_or_else(global_prefixes, guild_prefixes)
+    }
+
     /// Returns global storage directory if `guild_id` is `None`,
     /// otherwise returns guild storage directory by guild id.
     fn directory(&self, guild_id: Option<Id<GuildMarker>>) -> Directory {
@@ -170,6 +350,12 @@ impl<'a> Global<'a> {
     pub fn classic_prefix(&mut self) -> AnyResult<&Prefix> {
         Ok(&self.bot_settings()?.prefix)
     }
+
+    /// Get all global classic command prefixes.
+    pub fn classic_prefixes(&mut self) -> AnyResult<Prefixes> {
+        let s = self.bot_settings()?;
+        Ok(Prefixes::new(&s.prefix, &s.prefixes, s.prefix_ignore_case))
+    }
 }
 
 /// Guild data entry guard.
@@ -197,6 +383,17 @@ impl<'a> Guild<'a> {
         Ok(&self.settings()?.prefix)
     }
 
+    /// Get all guild classic command prefixes.
+    pub fn classic_prefixes(&mut self) -> AnyResult<Prefixes> {
+        let s = self.settings()?;
+        Ok(Prefixes::new(&s.prefix, &s.prefixes, s.prefix_ignore_case))
+    }
+
+    /// Get guild classic command aliases.
+    pub fn aliases(&mut self) -> AnyResult<&HashMap<String, String>> {
+        Ok(&self.settings()?.aliases)
+    }
+
     /// Get a reaction-roles configuration by channel and message ids.
     pub fn reaction_roles(
         &mut self,
@@ -391,6 +588,44 @@ impl AsRef<str> for Prefix {
     }
 }
 
+/// Set of classic command prefixes.
+#[derive(Debug, Clone)]
+pub struct Prefixes {
+    /// Prefixes, longest first so that the most specific one matches.
+    list: Vec<Prefix>,
+    /// If prefixes are matched case-insensitively.
+    ignore_case: bool,
+}
+
+impl Prefixes {
+    /// Create a set of prefixes from the main prefix and any additional ones.
+    pub fn new(prefix: &Prefix, additional: &[Prefix], ignore_case: bool) -> Self {
+        let mut list: Vec<_> = std::iter::once(prefix)
+            .chain(additional)
+            .filter(|p| !p.is_empty())
+            .cloned()
+            .collect();
+
+        list.sort_by(|a, b| {
+            b.len()
+                .cmp(&a.len())
+                .then_with(|| a.as_str().cmp(b.as_str()))
+        });
+        list.dedup_by(|a, b| a.as_str() == b.as_str());
+
+        Self { list, ignore_case }
+    }
+
+    /// Returns `Some((prefix, unprefixed))` if `text` starts with any of the prefixes.
+    pub fn unprefix<'a>(&self, text: &'a str) -> Option<(&'a str, &'a str)> {
+        if self.ignore_case {
+            parser::unprefix_with_ignore_case(&self.list, text)
+        } else {
+            parser::unprefix_with(&self.list, text)
+        }
+    }
+}
+
 /// Reaction-role mapping with the reaction type and role id.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReactionRole {
@@ -409,3 +644,53 @@ impl PartialEq for ReactionRole {
         utils::reaction_type_eq(&self.emoji, &other.emoji) && self.role == other.role
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permission_override_precedence() {
+        let guild_id = Id::new(1);
+        let (user, other) = (Id::new(2), Id::new(3));
+        let (moderator, muted) = (Id::new(4), Id::new(5));
+
+        let mut settings = GuildSettings::default();
+        settings
+            .permissions
+            .insert("roles".to_string(), PermissionOverrides {
+                allow_roles: HashSet::from([moderator]),
+                deny_roles: HashSet::from([guild_id.cast(), muted]),
+                ..Default::default()
+            });
+        settings
+            .permissions
+            .insert("roles add".to_string(), PermissionOverrides {
+                deny_users: HashSet::from([user]),
+                ..Default::default()
+            });
+        settings
+            .synced_permissions
+            .insert("voice".to_string(), PermissionOverrides {
+                allow_users: HashSet::from([user]),
+                ..Default::default()
+            });
+
+        let check = |user_id, roles: &[_], path: &[&str]| {
+            settings.permission_override(guild_id, user_id, roles, path)
+        };
+
+        // Allowed roles take precedence over denied ones, and `@everyone` applies last.
+        assert_eq!(Some(true), check(other, &[moderator, muted], &["roles"]));
+        assert_eq!(Some(false), check(other, &[muted], &["roles", "remove"]));
+        assert_eq!(Some(false), check(other, &[], &["roles"]));
+
+        // Subcommand overrides take precedence over the parent command.
+        assert_eq!(Some(false), check(user, &[moderator], &["roles", "add"]));
+        assert_eq!(Some(true), check(other, &[moderator], &["roles", "add"]));
+
+        // Synced permissions apply by command name.
+        assert_eq!(Some(true), check(user, &[], &["voice", "play"]));
+        assert_eq!(None, check(other, &[], &["voice", "play"]));
+    }
+}
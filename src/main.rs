@@ -25,7 +25,10 @@ use twilight_gateway::{
 };
 use twilight_http::client::InteractionClient;
 use twilight_http::Client;
-use twilight_model::application::interaction::{Interaction, InteractionData};
+use twilight_model::application::command::permissions::{
+    CommandPermissionType, GuildCommandPermissions,
+};
+use twilight_model::application::interaction::{Interaction, InteractionData, InteractionType};
 use twilight_model::channel::{Channel, Message};
 use twilight_model::gateway::payload::incoming::{
     ChannelUpdate, Hello, MessageDelete, MessageDeleteBulk, MessageUpdate, Ready, RoleUpdate,
@@ -34,15 +37,20 @@ use twilight_model::gateway::payload::outgoing::update_presence::UpdatePresenceP
 use twilight_model::gateway::presence::{ActivityType, MinimalActivity, Status};
 use twilight_model::gateway::{GatewayReaction, Intents};
 use twilight_model::guild::{Guild, Role};
-use twilight_model::id::marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker};
+use twilight_model::id::marker::{
+    ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker,
+};
 use twilight_model::id::Id;
 use twilight_model::oauth::Application;
-use twilight_model::user::CurrentUser;
+use twilight_model::user::{CurrentUser, User};
 use twilight_model::voice::VoiceState;
 use twilight_standby::Standby;
 
+use crate::commands::cooldown::Cooldowns;
+use crate::commands::incident::{Incident, IncidentId, Incidents};
+use crate::commands::replies::Replies;
 use crate::commands::{CommandError, Commands};
-use crate::config::BotConfig;
+use crate::config::{BotConfig, PermissionOverrides};
 use crate::utils::prelude::*;
 
 mod commands;
@@ -80,6 +88,12 @@ pub struct Context {
     cache: Arc<InMemoryCache>,
     /// Standby event system.
     standby: Arc<Standby>,
+    /// Command cooldown tracker.
+    cooldowns: Arc<Cooldowns>,
+    /// Incident report tracker.
+    incidents: Arc<Incidents>,
+    /// Classic command reply tracker.
+    replies: Arc<Replies>,
     /// Async runtime.
     runtime: Arc<Runtime>,
     /// Shard associated with the event.
@@ -95,6 +109,18 @@ impl Context {
         self.http.interaction(self.application.id)
     }
 
+    /// Returns true if the user is the bot owner or a member of the owner team.
+    pub fn is_owner(&self, user_id: Id<UserMarker>) -> bool {
+        // Team owned applications have a placeholder user as the owner.
+        if let Some(team) = &self.application.team {
+            team.members.iter().any(|m| m.user.id == user_id)
+        } else if let Some(owner) = &self.application.owner {
+            owner.id == user_id
+        } else {
+            false
+        }
+    }
+
     /// Get role objects with `ids` from cache or fetch from client.
     pub async fn roles_from(
         &self,
@@ -151,6 +177,14 @@ impl Context {
         }
     }
 
+    /// Get the user object from cache or fetch from client.
+    pub async fn user_from(&self, user_id: Id<UserMarker>) -> AnyResult<User> {
+        match self.cache.user(user_id) {
+            Some(user) => Ok(user.to_owned()),
+            None => Ok(self.http.user(user_id).send().await?),
+        }
+    }
+
     /// Search for a voice channel that a user is connected to in a guild.
     pub async fn user_voice_channel(
         &self,
@@ -302,6 +336,9 @@ async fn async_main(runtime: Arc<Runtime>) -> AnyResult<()> {
         user,
         cache,
         standby,
+        cooldowns: Arc::new(Cooldowns::default()),
+        incidents: Arc::new(Incidents::default()),
+        replies: Arc::new(Replies::default()),
         runtime,
         shard: None,
         #[cfg(feature = "voice")]
@@ -377,8 +414,13 @@ async fn shutdown_task(events_tx: BotEventSender) -> AnyResult<()> {
 }
 
 /// Main events handler.
-#[tracing::instrument(name = "events", skip_all, fields(event = event.kind().name()))]
+#[tracing::instrument(
+    name = "events",
+    skip_all,
+    fields(event = event.kind().name(), incident = tracing::field::Empty)
+)]
 async fn handle_event(ctx: Context, event: Event) -> AnyResult<()> {
+    let kind = event.kind();
     let result = match event {
         Event::Ready(r) => handle_ready(&ctx, *r).await,
         Event::GuildCreate(g) => handle_guild_create(&ctx, g.0).await,
@@ -390,13 +432,7 @@ async fn handle_event(ctx: Context, event: Event) -> AnyResult<()> {
         Event::ReactionAdd(r) => handle_reaction_add(&ctx, r.0).await,
         Event::ReactionRemove(r) => handle_reaction_remove(&ctx, r.0).await,
         Event::VoiceStateUpdate(v) => handle_voice_state(&ctx, v.0).await,
-        Event::CommandPermissionsUpdate(cpu) => {
-            debug!(
-                "Permissions update event: Command '{}' in guild '{}'",
-                cpu.id, cpu.guild_id
-            );
-            Ok(())
-        },
+        Event::CommandPermissionsUpdate(cpu) => handle_command_permissions(&ctx, cpu.0).await,
 
         // Gateway events.
         Event::GatewayHello(h) => handle_hello(&ctx, h).await,
@@ -427,17 +463,30 @@ async fn handle_event(ctx: Context, event: Event) -> AnyResult<()> {
     if let Err(e) = result {
         let chain = e.oneliner();
         eprintln!("Event error: {e:?}");
-        error!("Event error: {chain}");
 
-        if let Ok(id) = env::var("DISCORD_BOTDEV_CHANNEL") {
-            // Send error as message on bot dev channel.
-            let bot_dev = Id::new(id.parse()?);
-            ctx.http
-                .create_message(bot_dev)
-                .content(&format!("{e:?}"))?
-                .send()
-                .await?;
+        // Failed commands are reported where they are handled, and errors caused by users are not incidents.
+        if let Some(command_error) = CommandError::find(&e) {
+            if let Some(id) = command_error.incident() {
+                error!(incident = %id, "Event error: {chain}");
+                return Ok(());
+            }
+
+            if !matches!(
+                command_error.user_error(),
+                CommandError::Other(_) | CommandError::Multiple(_)
+            ) {
+                debug!("Event error: {chain}");
+                return Ok(());
+            }
         }
+
+        let id = IncidentId::new();
+        tracing::Span::current().record("incident", tracing::field::display(id));
+        error!(incident = %id, "Event error: {chain}");
+
+        // Send error report on bot dev channel.
+        let incident = Incident::new(id, format!("event '{kind:?}'"), &e);
+        ctx.incidents.report(&ctx, &incident).await?;
     }
 
     Ok(())
@@ -459,16 +508,12 @@ async fn handle_ready(ctx: &Context, ready: Ready) -> AnyResult<()> {
     println!("Ready: '{}'", ready.user.name);
     info!("Ready: '{}'", ready.user.name);
 
-    let commands = ctx.commands.twilight_commands()?;
-
-    debug!("Creating {} global commands", commands.len());
-
-    // Set global application commands.
-    ctx.http
-        .interaction(ctx.application.id)
-        .set_global_commands(&commands)
-        .send()
-        .await?;
+    // Register application commands once, instead of on every shard.
+    if ready.shard.map_or(true, |s| s.number() == 0) {
+        crate::commands::sync::register(ctx)
+            .await
+            .context("Failed to register application commands")?;
+    }
 
     Ok(())
 }
@@ -489,12 +534,6 @@ async fn handle_guild_create(ctx: &Context, guild: Guild) -> AnyResult<()> {
         }
     }
 
-    // ctx.http
-    //     .interaction(ctx.application.id)
-    //     .set_guild_commands(guild.id, &commands)
-    //     .send()
-    //     .await?;
-
     Ok(())
 }
 
@@ -504,19 +543,32 @@ async fn handle_interaction_create(ctx: &Context, mut inter: Interaction) -> Any
     // Take interaction data from the interaction,
     // so that both can be passed forward without matching again.
     match inter.data.take() {
-        Some(InteractionData::ApplicationCommand(d)) => {
-            println!("{d:#?}");
-            crate::commands::handle::application_command(ctx, inter, *d)
+        Some(InteractionData::ApplicationCommand(d))
+            if inter.kind == InteractionType::ApplicationCommandAutocomplete =>
+        {
+            crate::commands::handle::autocomplete(ctx, inter, *d)
                 .await
-                .context("Failed to handle application command")?;
+                .context("Failed to handle autocomplete")?;
+        },
+        Some(InteractionData::ApplicationCommand(d)) => {
+            trace!("{d:#?}");
+            match crate::commands::handle::application_command(ctx, inter, *d).await {
+                // Already responded to.
+                Err(
+                    CommandError::AccessDenied
+                    | CommandError::Cooldown { .. }
+                    | CommandError::Disabled,
+                ) => {},
+                res => res.context("Failed to handle application command")?,
+            }
         },
         Some(InteractionData::MessageComponent(d)) => {
-            println!("{d:#?}");
-            //
+            crate::commands::handle::message_component(ctx, inter, d)
+                .await
+                .context("Failed to handle message component")?;
         },
-        Some(InteractionData::ModalSubmit(d)) => {
-            println!("{d:#?}");
-            //
+        Some(InteractionData::ModalSubmit(_)) => {
+            // Modal submits are awaited by the request that opened the modal.
         },
         Some(d) => {
             println!("{d:#?}");
@@ -557,36 +609,56 @@ async fn handle_message_create(ctx: &Context, msg: Message) -> AnyResult<()> {
             }
             Ok(())
         },
-        Err(CommandError::AccessDenied) => {
-            ctx.http
-                .create_message(msg.channel_id)
-                .content("Rekt, you cannot use that. :melting_face:")?
-                .reply(msg.id)
-                .await?;
-            Ok(())
-        },
+        // Already replied to.
+        Err(
+            CommandError::AccessDenied | CommandError::Cooldown { .. } | CommandError::Disabled,
+        ) => Ok(()),
         res => res.context("Failed to handle classic command"),
     }
 }
 
-async fn handle_message_update(_ctx: &Context, _mu: MessageUpdate) -> AnyResult<()> {
-    // TODO Check if updated message is something that should update content from the bot.
-    Ok(())
+async fn handle_message_update(ctx: &Context, mu: MessageUpdate) -> AnyResult<()> {
+    // Only edited content of commands that were replied to is handled again.
+    if mu.content.is_none() || !ctx.replies.mark_stale(mu.id) {
+        return Ok(());
+    }
+
+    // The update may be partial, so the full message is fetched.
+    let msg = ctx.http.message(mu.channel_id, mu.id).send().await?;
+    let result = handle_message_create(ctx, msg).await;
+
+    // Remove the previous reply if the command did not reply again.
+    if let Some(reply_id) = ctx.replies.take_stale(mu.id) {
+        delete_reply(ctx, mu.channel_id, reply_id).await;
+    }
+
+    result
 }
 
 async fn handle_message_delete(ctx: &Context, md: MessageDelete) -> AnyResult<()> {
-    let Some(guild_id) = md.guild_id else {
-        return Ok(());
-    };
+    if let Some(guild_id) = md.guild_id {
+        // Remove reaction roles mappping, if deleted message was one.
+        ctx.config
+            .guild(guild_id)
+            .remove_reaction_roles(md.channel_id, md.id)?;
+    }
 
-    // Remove reaction roles mappping, if deleted message was one.
-    ctx.config
-        .guild(guild_id)
-        .remove_reaction_roles(md.channel_id, md.id)?;
+    // Remove the reply to a deleted command message.
+    if let Some(reply_id) = ctx.replies.remove(md.id) {
+        delete_reply(ctx, md.channel_id, reply_id).await;
+    }
+    ctx.replies.remove_reply(md.id);
 
     Ok(())
 }
 
+/// Delete a reply to a command message, which may have been deleted already.
+async fn delete_reply(ctx: &Context, channel_id: Id<ChannelMarker>, reply_id: Id<MessageMarker>) {
+    if let Err(e) = ctx.http.delete_message(channel_id, reply_id).await {
+        warn!("Failed to delete reply '{reply_id}': {e}");
+    }
+}
+
 async fn handle_message_delete_bulk(ctx: &Context, mdb: MessageDeleteBulk) -> AnyResult<()> {
     let message_delete_with = |id| MessageDelete {
         channel_id: mdb.channel_id,
@@ -714,6 +786,53 @@ async fn handle_reaction_remove(ctx: &Context, reaction: GatewayReaction) -> Any
     Ok(())
 }
 
+async fn handle_command_permissions(
+    ctx: &Context,
+    permissions: GuildCommandPermissions,
+) -> AnyResult<()> {
+    let guild_id = permissions.guild_id;
+
+    // Permissions of all commands are updated with the application id as the command id.
+    let name = if permissions.id.cast() == ctx.application.id {
+        String::new()
+    } else {
+        let client = ctx.interaction();
+        match client.global_command(permissions.id).send().await {
+            Ok(cmd) => cmd.name,
+            Err(_) => {
+                client
+                    .guild_command(guild_id, permissions.id)
+                    .send()
+                    .await?
+                    .name
+            },
+        }
+    };
+
+    debug!("Permissions update event: Command '{name}' in guild '{guild_id}'");
+
+    let mut overrides = PermissionOverrides::default();
+    for p in permissions.permissions.iter() {
+        match (p.id, p.permission) {
+            (CommandPermissionType::Role(id), true) => overrides.allow_roles.insert(id),
+            (CommandPermissionType::Role(id), false) => overrides.deny_roles.insert(id),
+            (CommandPermissionType::User(id), true) => overrides.allow_users.insert(id),
+            (CommandPermissionType::User(id), false) => overrides.deny_users.insert(id),
+            // Channel restrictions are managed with the guild command settings.
+            (CommandPermissionType::Channel(_), _) => false,
+        };
+    }
+
+    ctx.config.guild_settings_with(guild_id, |s| {
+        if overrides.is_empty() {
+            s.synced_permissions.remove(&name);
+        } else {
+            s.synced_permissions.insert(name.clone(), overrides.clone());
+        }
+        Ok(())
+    })
+}
+
 async fn handle_voice_state(_ctx: &Context, _voice: VoiceState) -> AnyResult<()> {
     // println!("{voice:#?}",);
     Ok(())
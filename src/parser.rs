@@ -1,6 +1,8 @@
 //! Functions for parsing arguments.
 #![allow(dead_code)]
 
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::str::pattern::{Pattern, ReverseSearcher};
 
 use crate::commands::{CommandError, CommandResult};
@@ -26,6 +28,26 @@ where
     None
 }
 
+/// Same as [`unprefix_with`](unprefix_with), but prefixes are matched ASCII case-insensitively.
+pub fn unprefix_with_ignore_case<I, T>(prefixes: I, text: &str) -> Option<(&str, &str)>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+{
+    for prefix in prefixes {
+        let len = prefix.as_ref().len();
+        let matched = text
+            .get(..len)
+            .map_or(false, |start| start.eq_ignore_ascii_case(prefix.as_ref()));
+
+        if matched {
+            return Some((&text[..len], &text[len..]));
+        }
+    }
+
+    None
+}
+
 /// Returns a tuple of `(next, rest)`, where `next` is the part before any whitespaces and `rest` is everything after any whitespaces.
 pub fn split_once_whitespace(text: &str) -> (&str, Option<&str>) {
     text.split_once(char::is_whitespace)
@@ -33,9 +55,73 @@ pub fn split_once_whitespace(text: &str) -> (&str, Option<&str>) {
     // .unwrap_or((text, ""))
 }
 
+/// Returns `text` with the first word replaced by its alias target, keeping the rest as is.
+/// Returns `None` if the first word is not an alias.
+/// The target is not expanded again, so aliases cannot recurse.
+pub fn expand_alias(aliases: &HashMap<String, String>, text: &str) -> Option<String> {
+    let (name, rest) = split_once_whitespace(text);
+    let target = aliases.get(name)?;
+
+    Some(match rest {
+        Some(rest) => format!("{target} {rest}"),
+        None => target.to_owned(),
+    })
+}
+
+/// Returns the edit distance between `a` and `b`, counting insertions, deletions,
+/// substitutions and transpositions of adjacent characters.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // Only the last two rows of the distance matrix are needed.
+    let mut prev2 = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        curr[0] = i;
+
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            curr[j] = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                curr[j] = curr[j].min(prev2[j - 2] + 1);
+            }
+        }
+
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+/// Returns `candidates` that are close enough to `target` by edit distance, the closest first.
+/// Roughly one edit is allowed per three characters of `target`.
+pub fn closest_matches<'a, I>(target: &str, candidates: I) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let target = target.to_lowercase();
+    let max = (target.chars().count() + 2) / 3;
+
+    let mut matches: Vec<_> = candidates
+        .into_iter()
+        .filter_map(|c| {
+            let distance = edit_distance(&target, &c.to_lowercase());
+            (distance <= max).then_some((distance, c))
+        })
+        .collect();
+    matches.sort_unstable();
+
+    matches.into_iter().map(|(_, c)| c).collect()
+}
+
 /// Try to parse string-slice into arg parts.
 /// For more details about individual argument parsing, see [`maybe_quoted_arg`](maybe_quoted_arg)
-pub fn parse_args(mut input: &str) -> Result<Vec<&str>, CommandError> {
+pub fn parse_args(mut input: &str) -> Result<Vec<Cow<'_, str>>, CommandError> {
     let mut args = Vec::new();
 
     loop {
@@ -61,41 +147,174 @@ pub fn parse_args(mut input: &str) -> Result<Vec<&str>, CommandError> {
 /// or the whole input (after `trim_start`).
 /// The `Option` will contain the remaining text, if any.
 /// # Notes
-/// - Escape characters are **not** handled.
+/// - A backslash escapes the next character if it is a backslash, a delimiter or a whitespace,
+/// both inside and outside of quotes. Other backslashes are kept as is.
 /// - If a non-quoted argument contains any delimiters before any whitespace,
 /// those characters (and everything upto a whitespace or the end) will be in the `arg`.
 /// - If a quoted argument is followed by any character (whitespace or not),
 /// those characters will be in the remaining `Option`.
-pub fn maybe_quoted_arg(input: &str) -> Result<(&str, Option<&str>), CommandError> {
+pub fn maybe_quoted_arg(input: &str) -> Result<(Cow<'_, str>, Option<&str>), CommandError> {
     // First trim off any leading whitespace.
     let input = input.trim_start();
 
-    // Indexing a string is in bytes, so enumerate the bytes.
-    let mut bytes = input.bytes().enumerate();
-
-    // Get the first byte or return an error for a missing argument.
-    let (_, initial) = bytes.next().ok_or(CommandError::MissingArgs)?;
-
-    // Check if the first byte is a delimiter character (assuming all delimiter characters are one byte wide utf-8).
-    if consts::DELIMITERS.contains(&(initial as char)) {
-        // Find the matching pair.
-        let idx = bytes
-            .filter(|(i, _)| input.is_char_boundary(*i))
-            .find_map(|(i, b)| (b == initial).then_some(i))
-            .ok_or_else(|| {
-                let input = utils::escape_discord_chars(input);
-                CommandError::ParseError(format!(
-                    "Missing matching delimiter: '{input}', expected one of: {}.",
-                    utils::nice_list(consts::DELIMITERS)
-                ))
+    let mut chars = input.char_indices().peekable();
+
+    // Get the first character or return an error for a missing argument.
+    let &(_, initial) = chars.peek().ok_or(CommandError::MissingArgs)?;
+
+    // Quoted argument ends at the matching delimiter, otherwise at the first whitespace.
+    let quote = consts::DELIMITERS.contains(&initial).then_some(initial);
+    let start = match quote {
+        Some(quote) => {
+            chars.next(); // Skip the opening delimiter.
+            quote.len_utf8()
+        },
+        None => 0,
+    };
+
+    // Only allocate if there are any escapes.
+    let mut unescaped: Option<String> = None;
+    let mut end = None;
+
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            if let Some(&(_, next)) = chars.peek().filter(|(_, n)| is_escapable(*n)) {
+                unescaped
+                    .get_or_insert_with(|| input[start..i].to_owned())
+                    .push(next);
+                chars.next();
+                continue;
+            }
+        }
+
+        let is_end = match quote {
+            Some(quote) => c == quote,
+            None => c.is_whitespace(),
+        };
+
+        if is_end {
+            end = Some((i, c));
+            break;
+        }
+
+        if let Some(s) = unescaped.as_mut() {
+            s.push(c);
+        }
+    }
+
+    let (arg_end, rest) = match (end, quote) {
+        (Some((i, c)), _) => (i, input.get(i + c.len_utf8()..)),
+        (None, None) => (input.len(), None),
+        (None, Some(_)) => {
+            let input = utils::escape_discord_chars(input);
+            return Err(CommandError::ParseError(format!(
+                "Missing matching delimiter: '{input}', expected one of: {}.",
+                utils::nice_list(consts::DELIMITERS)
+            )));
+        },
+    };
+
+    let arg = unescaped.map_or_else(|| Cow::Borrowed(&input[start..arg_end]), Cow::Owned);
+
+    Ok((arg, rest))
+}
+
+/// Named flag in command arguments, given as `--name value`, `--name=value` or just `--name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flag<'a> {
+    pub name: &'a str,
+    pub value: Option<Cow<'a, str>>,
+}
+
+/// Split named flags from the positional arguments in `input`.
+/// Returns the flags and the text of the positional arguments.
+/// # Notes
+/// - A flag without `=` takes the next argument as its value, if `takes_value(name)` returns true.
+/// - Flags are not searched after a lone `--`, or from the positional argument at index `n`
+/// if `is_rest(flags, n)` returns true, so that text consuming arguments are kept as is.
+/// - Quoted arguments and arguments such as `-->` are not flags.
+/// # Errors
+/// Returns `CommandError::UnexpectedArgs` if a flag is given more than once or is missing a value.
+pub fn split_flags<'a, V, R>(
+    input: &'a str,
+    takes_value: V,
+    is_rest: R,
+) -> CommandResult<(Vec<Flag<'a>>, String)>
+where
+    V: Fn(&str) -> bool,
+    R: Fn(&[Flag<'a>], usize) -> bool,
+{
+    let mut flags: Vec<Flag<'a>> = Vec::new();
+    let mut positional = String::new();
+    let mut count = 0;
+    let mut rest = Some(input);
+
+    while let Some(text) = rest.filter(|t| !t.trim().is_empty()) {
+        if is_rest(&flags, count) {
+            positional.push_str(text);
+            break;
+        }
+
+        let text = text.trim_start();
+        let (arg, next) = maybe_quoted_arg(text)?;
+        let raw = &text[..text.len() - next.map_or(0, str::len)];
+        rest = next;
+
+        let flag = match raw.trim_end().strip_prefix("--") {
+            Some("") => {
+                positional.push_str(next.unwrap_or_default());
+                break;
+            },
+            Some(flag) if flag.starts_with(char::is_alphabetic) => flag,
+            _ => {
+                positional.push_str(raw);
+                count += 1;
+                continue;
+            },
+        };
+
+        let (name, mut value) = match flag.split_once('=') {
+            Some((name, _)) => {
+                let value = match arg {
+                    Cow::Borrowed(a) => Cow::Borrowed(a.split_once('=').map_or("", |(_, v)| v)),
+                    Cow::Owned(a) => Cow::Owned(a.split_once('=').map_or("", |(_, v)| v).into()),
+                };
+                (name, Some(value))
+            },
+            None => (flag, None),
+        };
+
+        if flags.iter().any(|f| f.name == name) {
+            return Err(CommandError::UnexpectedArgs(format!(
+                "Flag '--{name}' is given more than once"
+            )));
+        }
+
+        if value.is_none() && takes_value(name) {
+            let (arg, next) = maybe_quoted_arg(rest.unwrap_or_default()).map_err(|e| match e {
+                CommandError::MissingArgs => {
+                    CommandError::UnexpectedArgs(format!("Flag '--{name}' needs a value"))
+                },
+                e => e,
             })?;
+            value = Some(arg);
+            rest = next;
+        }
 
-        // Return everything between the two and then everything after, if any.
-        Ok((&input[1..idx], input.get(idx + 1..)))
-    } else {
-        // Did not start with a delimiter, try to split by whitespace instead.
-        Ok(split_once_whitespace(input))
+        flags.push(Flag { name, value });
+
+        // Keep the arguments around the flag apart.
+        if !positional.is_empty() && !positional.ends_with(char::is_whitespace) {
+            positional.push(' ');
+        }
     }
+
+    Ok((flags, positional))
+}
+
+/// Returns true if the character can be escaped with a backslash.
+fn is_escapable(c: char) -> bool {
+    c == '\\' || c.is_whitespace() || consts::DELIMITERS.contains(&c)
 }
 
 /// Returns a string-slice without delimiters, or returns ´input´ if no delimiters are found or can be stripped.
@@ -146,11 +365,15 @@ mod tests {
 
     use super::*;
 
+    fn cows<'a>(args: &[&'a str]) -> Vec<Cow<'a, str>> {
+        args.iter().map(|&a| Cow::Borrowed(a)).collect()
+    }
+
     #[test]
     fn overly_ugly_arguments() {
-        let s = r#"    foo    bar "baz\n    `.-_' thing" abc-goo'`" "sample text \\\"* ;    "#;
+        let s = r#"    foo    bar "baz\n    `.-_' thing" abc-goo'`" "sample text \\\\\\"* ;    "#;
         assert_eq!(
-            Ok(vec![
+            Ok(cows(&[
                 r#"foo"#,
                 r#"bar"#,
                 r#"baz\n    `.-_' thing"#,
@@ -158,7 +381,7 @@ mod tests {
                 r#"sample text \\\"#,
                 r#"*"#,
                 r#";"#,
-            ]),
+            ])),
             parse_args(s)
         );
     }
@@ -175,15 +398,132 @@ mod tests {
     #[test]
     fn parse_one_arg() {
         let s = r#"    foo    bar"#;
-        assert_eq!(Ok(("foo", Some(r#"   bar"#))), maybe_quoted_arg(s));
+        assert_eq!(Ok(("foo".into(), Some(r#"   bar"#))), maybe_quoted_arg(s));
 
         let s = r#"foo bar"#;
-        assert_eq!(Ok(("foo", Some(r#"bar"#))), maybe_quoted_arg(s));
+        assert_eq!(Ok(("foo".into(), Some(r#"bar"#))), maybe_quoted_arg(s));
 
         let s = r#"    "foo"bar "#;
-        assert_eq!(Ok(("foo", Some(r#"bar "#))), maybe_quoted_arg(s));
+        assert_eq!(Ok(("foo".into(), Some(r#"bar "#))), maybe_quoted_arg(s));
 
         let s = r#""foo" bar "#;
-        assert_eq!(Ok(("foo", Some(r#" bar "#))), maybe_quoted_arg(s));
+        assert_eq!(Ok(("foo".into(), Some(r#" bar "#))), maybe_quoted_arg(s));
+    }
+
+    #[test]
+    fn escaped_arguments() {
+        let s = r#"foo\ bar "baz \"qux\" \\" 'it\'s' \"quoted\" C:\path"#;
+        assert_eq!(
+            Ok(cows(&[
+                r#"foo bar"#,
+                r#"baz "qux" \"#,
+                r#"it's"#,
+                r#""quoted""#,
+                r#"C:\path"#,
+            ])),
+            parse_args(s)
+        );
+
+        let s = r#"foo\ bar baz"#;
+        assert_eq!(
+            Ok((Cow::Owned("foo bar".to_string()), Some("baz"))),
+            maybe_quoted_arg(s)
+        );
+
+        let s = r#""foo\""#;
+        assert!(maybe_quoted_arg(s).is_err());
+    }
+
+    #[test]
+    fn split_named_flags() {
+        let s = r#"<@1> --seconds 300 --loud "a b" --x=1"#;
+        let (flags, positional) = split_flags(s, |name| name != "loud", |_, _| false).unwrap();
+        assert_eq!(
+            vec![
+                Flag {
+                    name: "seconds",
+                    value: Some("300".into())
+                },
+                Flag {
+                    name: "loud",
+                    value: None
+                },
+                Flag {
+                    name: "x",
+                    value: Some("1".into())
+                },
+            ],
+            flags
+        );
+        assert_eq!(r#"<@1> "a b" "#, positional);
+
+        // Flags are not searched after a lone `--` or in the rest.
+        let (flags, positional) = split_flags("--x 1 -- --y", |_| true, |_, _| false).unwrap();
+        assert_eq!(1, flags.len());
+        assert_eq!("--y", positional);
+
+        let (flags, positional) = split_flags("say --not a flag", |_| true, |_, n| n >= 1).unwrap();
+        assert!(flags.is_empty());
+        assert_eq!("say --not a flag", positional);
+
+        assert!(split_flags("--x 1 --x 2", |_| true, |_, _| false).is_err());
+        assert!(split_flags("--x", |_| true, |_, _| false).is_err());
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(0, edit_distance("help", "help"));
+        assert_eq!(1, edit_distance("hlep", "help"));
+        assert_eq!(1, edit_distance("hel", "help"));
+        assert_eq!(2, edit_distance("vocie", "voices"));
+        assert_eq!(4, edit_distance("", "help"));
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+    }
+
+    #[test]
+    fn closest_command_matches() {
+        let names = ["help", "hello", "voice", "time", "about"];
+
+        assert_eq!(vec!["help"], closest_matches("hlep", names));
+        assert_eq!(vec!["hello", "help"], closest_matches("helo", names));
+        assert_eq!(vec!["voice"], closest_matches("Vocie", names));
+        assert_eq!(Vec::<&str>::new(), closest_matches("xyz", names));
+    }
+
+    #[test]
+    fn unprefix_ignore_case() {
+        assert_eq!(
+            Some(("Hey ", "help")),
+            unprefix_with_ignore_case(["hey "], "Hey help")
+        );
+        assert_eq!(
+            Some(("!", "help")),
+            unprefix_with_ignore_case(["?", "!"], "!help")
+        );
+        assert_eq!(None, unprefix_with_ignore_case(["hey "], "help"));
+        assert_eq!(None, unprefix_with(["hey "], "Hey help"));
+    }
+
+    #[test]
+    fn expand_aliases() {
+        let aliases = HashMap::from([
+            ("fuel60".to_string(), "fuel 60".to_string()),
+            ("loop".to_string(), "loop again".to_string()),
+        ]);
+
+        assert_eq!(
+            Some("fuel 60".to_string()),
+            expand_alias(&aliases, "fuel60")
+        );
+        assert_eq!(
+            Some("fuel 60 extra args".to_string()),
+            expand_alias(&aliases, "fuel60 extra args")
+        );
+        assert_eq!(
+            Some("loop again".to_string()),
+            expand_alias(&aliases, "loop")
+        );
+        assert_eq!(None, expand_alias(&aliases, "fuel 60"));
+        assert_eq!(None, expand_alias(&aliases, ""));
     }
 }
@@ -4,7 +4,10 @@ use std::borrow::Cow;
 use std::fmt::Display;
 
 use serde::Serialize;
-use twilight_http::request::application::command::{SetGlobalCommands, SetGuildCommands};
+use twilight_http::request::application::command::{
+    GetGlobalCommand, GetGlobalCommands, GetGuildCommand, GetGuildCommands, SetGlobalCommands,
+    SetGuildCommands,
+};
 use twilight_http::request::application::interaction::{CreateFollowup, UpdateResponse};
 use twilight_http::request::channel::message::{
     CreateMessage, GetChannelMessages, GetChannelMessagesConfigured, GetMessage, UpdateMessage,
@@ -14,7 +17,9 @@ use twilight_http::request::guild::emoji::GetEmojis;
 use twilight_http::request::guild::member::GetMember;
 use twilight_http::request::guild::role::GetGuildRoles;
 use twilight_http::request::guild::{GetGuild, GetGuildChannels};
-use twilight_http::request::user::{GetCurrentUser, GetCurrentUserGuildMember, GetUser};
+use twilight_http::request::user::{
+    CreatePrivateChannel, GetCurrentUser, GetCurrentUserGuildMember, GetUser,
+};
 use twilight_http::request::GetUserApplicationInfo;
 use twilight_model::application::command::Command;
 use twilight_model::channel::message::ReactionType;
@@ -84,14 +89,19 @@ macro impl_exec_model_ext($req:ty, $val:ty) {
 
 impl_exec_model_ext!(CreateFollowup<'_>, Message);
 impl_exec_model_ext!(CreateMessage<'_>, Message);
+impl_exec_model_ext!(CreatePrivateChannel<'_>, Channel);
 impl_exec_model_ext!(GetChannel<'_>, Channel);
 impl_exec_model_ext!(GetChannelMessages<'_>, Vec<Message>);
 impl_exec_model_ext!(GetChannelMessagesConfigured<'_>, Vec<Message>);
 impl_exec_model_ext!(GetCurrentUser<'_>, CurrentUser);
 impl_exec_model_ext!(GetCurrentUserGuildMember<'_>, Member);
 impl_exec_model_ext!(GetEmojis<'_>, Vec<Emoji>);
+impl_exec_model_ext!(GetGlobalCommand<'_>, Command);
+impl_exec_model_ext!(GetGlobalCommands<'_>, Vec<Command>);
 impl_exec_model_ext!(GetGuild<'_>, Guild);
 impl_exec_model_ext!(GetGuildChannels<'_>, Vec<Channel>);
+impl_exec_model_ext!(GetGuildCommand<'_>, Command);
+impl_exec_model_ext!(GetGuildCommands<'_>, Vec<Command>);
 impl_exec_model_ext!(GetGuildRoles<'_>, Vec<Role>);
 impl_exec_model_ext!(GetMember<'_>, Member);
 impl_exec_model_ext!(GetMessage<'_>, Message);